@@ -0,0 +1,14 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS quest_logs
+(
+	id          BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	user_id     BIGINT NOT NULL,
+	scenario_id BIGINT NOT NULL,
+	victory     BOOLEAN NOT NULL,
+	created_at  TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at  TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(user_id) REFERENCES users(id),
+	FOREIGN KEY(scenario_id) REFERENCES scenarios(id)
+);
+
+CREATE INDEX ON quest_logs (user_id, scenario_id);
@@ -0,0 +1,11 @@
+-- Add migration script here
+ALTER TABLE quest_logs RENAME TO plays;
+ALTER INDEX quest_logs_user_id_scenario_id_idx RENAME TO plays_user_id_scenario_id_idx;
+
+ALTER TABLE plays
+	ADD COLUMN event_id   BIGINT REFERENCES events(id),
+	ADD COLUMN players    SMALLINT CHECK (players BETWEEN 1 AND 4),
+	ADD COLUMN difficulty VARCHAR(255) NOT NULL DEFAULT 'normal' CHECK (difficulty IN ('easy', 'normal', 'nightmare')),
+	ADD COLUMN notes      TEXT;
+
+CREATE INDEX ON plays (scenario_id);
@@ -0,0 +1,21 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS scores
+(
+	id                 BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	user_id            BIGINT NOT NULL,
+	scenario_id        BIGINT NOT NULL,
+	event_id           BIGINT,
+	final_threat       INTEGER NOT NULL,
+	dead_heroes_threat INTEGER NOT NULL,
+	damage             INTEGER NOT NULL,
+	rounds             INTEGER NOT NULL,
+	victory_points     INTEGER NOT NULL,
+	score              INTEGER NOT NULL,
+	created_at         TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at         TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(user_id) REFERENCES users(id),
+	FOREIGN KEY(scenario_id) REFERENCES scenarios(id),
+	FOREIGN KEY(event_id) REFERENCES events(id)
+);
+
+CREATE INDEX ON scores (event_id, scenario_id, score);
@@ -0,0 +1,8 @@
+-- Add migration script here
+ALTER TABLE sets
+ADD COLUMN product_type VARCHAR(255),
+ADD COLUMN cycle VARCHAR(255);
+
+ALTER TABLE scenarios
+ADD COLUMN difficulty SMALLINT,
+ADD CONSTRAINT scenarios_difficulty_check CHECK (difficulty BETWEEN 1 AND 10);
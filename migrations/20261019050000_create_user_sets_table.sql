@@ -0,0 +1,13 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS user_sets
+(
+	id         BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	user_id    BIGINT NOT NULL,
+	set_id     BIGINT NOT NULL,
+	created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(user_id) REFERENCES users(id),
+	FOREIGN KEY(set_id) REFERENCES sets(id)
+);
+
+CREATE UNIQUE INDEX ON user_sets (user_id, set_id);
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE events ADD COLUMN seed VARCHAR(255);
@@ -0,0 +1,37 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS gauntlet_runs
+(
+	id          BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	scenario_id BIGINT,
+	seed        VARCHAR(255) NOT NULL,
+	victory     BOOLEAN,
+	created_at  TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at  TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(scenario_id) REFERENCES scenarios(id)
+);
+
+CREATE TABLE IF NOT EXISTS gauntlet_runs_challenges
+(
+	id              BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	gauntlet_run_id BIGINT NOT NULL,
+	challenge_id    BIGINT NOT NULL,
+	created_at      TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at      TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(gauntlet_run_id) REFERENCES gauntlet_runs(id),
+	FOREIGN KEY(challenge_id) REFERENCES challenges(id)
+);
+
+CREATE UNIQUE INDEX ON gauntlet_runs_challenges (gauntlet_run_id, challenge_id);
+
+CREATE TABLE IF NOT EXISTS gauntlet_runs_users
+(
+	id              BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	gauntlet_run_id BIGINT NOT NULL,
+	user_id         BIGINT NOT NULL,
+	created_at      TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at      TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(gauntlet_run_id) REFERENCES gauntlet_runs(id),
+	FOREIGN KEY(user_id) REFERENCES users(id)
+);
+
+CREATE UNIQUE INDEX ON gauntlet_runs_users (gauntlet_run_id, user_id);
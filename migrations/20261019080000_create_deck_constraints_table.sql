@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS deck_constraints
+(
+	id          BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	name        VARCHAR(255) NOT NULL,
+	code        VARCHAR(255) NOT NULL UNIQUE,
+	description TEXT,
+	category    VARCHAR(255) NOT NULL,
+	created_at  TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at  TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
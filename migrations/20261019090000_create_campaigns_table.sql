@@ -0,0 +1,41 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS campaigns
+(
+	id             BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	user_id        BIGINT NOT NULL,
+	cycle          VARCHAR(255) NOT NULL,
+	threat_penalty INTEGER NOT NULL DEFAULT 0,
+	active         BOOLEAN NOT NULL DEFAULT true,
+	created_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(user_id) REFERENCES users(id)
+);
+
+CREATE UNIQUE INDEX ON campaigns (user_id)
+WHERE active = true;
+
+CREATE TABLE IF NOT EXISTS campaign_scenarios
+(
+	id             BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	campaign_id    BIGINT NOT NULL,
+	scenario_id    BIGINT NOT NULL,
+	victory        BOOLEAN NOT NULL,
+	threat_penalty INTEGER NOT NULL DEFAULT 0,
+	created_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(campaign_id) REFERENCES campaigns(id),
+	FOREIGN KEY(scenario_id) REFERENCES scenarios(id)
+);
+
+CREATE TABLE IF NOT EXISTS campaign_log_entries
+(
+	id                   BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	campaign_id          BIGINT NOT NULL,
+	campaign_scenario_id BIGINT NOT NULL,
+	kind                 VARCHAR(255) NOT NULL CHECK (kind IN ('boon', 'burden', 'fallen_hero')),
+	name                 VARCHAR(255) NOT NULL,
+	created_at           TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at           TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(campaign_id) REFERENCES campaigns(id),
+	FOREIGN KEY(campaign_scenario_id) REFERENCES campaign_scenarios(id)
+);
@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS scenario_aliases
+(
+	id          BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	scenario_id BIGINT NOT NULL,
+	alias       VARCHAR(255) NOT NULL,
+	created_at  TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at  TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(scenario_id) REFERENCES scenarios(id)
+);
+
+CREATE UNIQUE INDEX ON scenario_aliases (LOWER(alias));
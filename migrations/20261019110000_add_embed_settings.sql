@@ -0,0 +1,12 @@
+-- Add migration script here
+ALTER TABLE events ADD COLUMN color INTEGER;
+ALTER TABLE events ADD COLUMN thumbnail_url VARCHAR(255);
+
+CREATE TABLE IF NOT EXISTS guild_settings
+(
+	id         BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	guild_id   BIGINT NOT NULL UNIQUE,
+	plain_text BOOLEAN NOT NULL DEFAULT false,
+	created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE events ADD COLUMN status_channel_id BIGINT;
+ALTER TABLE events ADD COLUMN status_message_id BIGINT;
+
+ALTER TABLE events_scenarios ADD COLUMN completed_at TIMESTAMP;
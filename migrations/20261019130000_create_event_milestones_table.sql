@@ -0,0 +1,15 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS event_milestones
+(
+	id           BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	event_id     BIGINT NOT NULL,
+	kind         VARCHAR(255) NOT NULL,
+	percent      SMALLINT NOT NULL,
+	role_id      BIGINT,
+	announced_at TIMESTAMP,
+	created_at   TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at   TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(event_id) REFERENCES events(id)
+);
+
+CREATE UNIQUE INDEX ON event_milestones (event_id, kind, percent);
@@ -0,0 +1,43 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS events_scenarios_users
+(
+	id                  BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	events_scenarios_id BIGINT NOT NULL,
+	user_id             BIGINT NOT NULL,
+	created_at          TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at          TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(events_scenarios_id) REFERENCES events_scenarios(id),
+	FOREIGN KEY(user_id) REFERENCES users(id)
+);
+
+CREATE UNIQUE INDEX ON events_scenarios_users (events_scenarios_id, user_id);
+
+CREATE TABLE IF NOT EXISTS event_rewards
+(
+	id         BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	event_id   BIGINT NOT NULL,
+	role_id    BIGINT NOT NULL,
+	challenges INTEGER,
+	set_id     BIGINT,
+	created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(event_id) REFERENCES events(id),
+	FOREIGN KEY(set_id) REFERENCES sets(id),
+	CHECK ((challenges IS NULL) <> (set_id IS NULL)),
+	CHECK (challenges > 0)
+);
+
+CREATE UNIQUE INDEX ON event_rewards (event_id, role_id);
+
+CREATE TABLE IF NOT EXISTS event_reward_grants
+(
+	id         BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	reward_id  BIGINT NOT NULL,
+	user_id    BIGINT NOT NULL,
+	created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(reward_id) REFERENCES event_rewards(id) ON DELETE CASCADE,
+	FOREIGN KEY(user_id) REFERENCES users(id)
+);
+
+CREATE UNIQUE INDEX ON event_reward_grants (reward_id, user_id);
@@ -0,0 +1,51 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS achievements
+(
+	id             BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	code           VARCHAR(255) NOT NULL UNIQUE,
+	name           VARCHAR(255) NOT NULL,
+	description    TEXT NOT NULL,
+	source         VARCHAR(255) NOT NULL,
+	victory        BOOLEAN,
+	difficulty     VARCHAR(255),
+	cycle          VARCHAR(255),
+	set_name       VARCHAR(255),
+	count          INTEGER NOT NULL DEFAULT 1,
+	every_scenario BOOLEAN NOT NULL DEFAULT false,
+	created_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	CHECK (source IN ('play', 'gauntlet', 'challenge', 'quest')),
+	CHECK (difficulty IN ('easy', 'normal', 'nightmare')),
+	CHECK (count > 0)
+);
+
+CREATE TABLE IF NOT EXISTS user_achievements
+(
+	id             BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	user_id        BIGINT NOT NULL,
+	achievement_id BIGINT NOT NULL,
+	progress       INTEGER NOT NULL DEFAULT 0,
+	earned_at      TIMESTAMP,
+	created_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(user_id) REFERENCES users(id),
+	FOREIGN KEY(achievement_id) REFERENCES achievements(id)
+);
+
+CREATE UNIQUE INDEX ON user_achievements (user_id, achievement_id);
+
+-- the scenarios already counted towards achievements that need every scenario of a set or cycle
+CREATE TABLE IF NOT EXISTS user_achievement_scenarios
+(
+	id             BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	user_id        BIGINT NOT NULL,
+	achievement_id BIGINT NOT NULL,
+	scenario_id    BIGINT NOT NULL,
+	created_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	updated_at     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY(user_id) REFERENCES users(id),
+	FOREIGN KEY(achievement_id) REFERENCES achievements(id),
+	FOREIGN KEY(scenario_id) REFERENCES scenarios(id)
+);
+
+CREATE UNIQUE INDEX ON user_achievement_scenarios (user_id, achievement_id, scenario_id);
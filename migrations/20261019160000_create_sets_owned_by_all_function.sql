@@ -0,0 +1,13 @@
+-- Add migration script here
+CREATE OR REPLACE FUNCTION sets_owned_by_all(discord_ids BIGINT[])
+RETURNS TABLE (set_id BIGINT)
+LANGUAGE SQL
+STABLE
+AS $$
+	SELECT user_sets.set_id
+	FROM user_sets, users
+	WHERE user_sets.user_id = users.id
+		AND users.discord_id = ANY(discord_ids)
+	GROUP BY user_sets.set_id
+	HAVING COUNT(DISTINCT users.id) = CARDINALITY(discord_ids)
+$$;
@@ -18,33 +18,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let scenarios = fetch().await?;
     let mut sets = HashMap::new();
     for scenario in scenarios {
-        let queried_set = sqlx::query!(
+        // keep the product metadata up to date for sets that were already loaded
+        let set_id = sqlx::query!(
             r#"
-SELECT id
-FROM sets
-WHERE name = $1;
-"#,
-            scenario.set
-        )
-        .fetch_optional(&pool)
-        .await?;
-
-        let set_id = if let Some(set) = queried_set {
-            set.id
-        } else {
-            let set = sqlx::query!(
-                r#"
-INSERT INTO sets ( name )
-VALUES ( $1 )
+INSERT INTO sets ( name, product_type, cycle )
+VALUES ( $1, $2, $3 )
+ON CONFLICT (name)
+DO
+    UPDATE SET product_type = $2,
+        cycle = $3,
+        updated_at = CURRENT_TIMESTAMP
 RETURNING id;
 "#,
-                scenario.set
-            )
-            .fetch_one(&pool)
-            .await?;
-
-            set.id
-        };
+            scenario.set,
+            scenario.product_type.as_str(),
+            scenario.cycle
+        )
+        .fetch_one(&pool)
+        .await?
+        .id;
 
         sets.insert(scenario.set.clone(), set_id);
 
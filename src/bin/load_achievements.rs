@@ -0,0 +1,85 @@
+use erebor_record_keeper::models::play::Difficulty;
+use serde::Deserialize;
+use sqlx::postgres::PgPoolOptions;
+
+#[derive(Deserialize)]
+struct Achievements {
+    achievement: Vec<Achievement>,
+}
+
+#[derive(Deserialize)]
+struct Achievement {
+    code: String,
+    name: String,
+    description: String,
+    source: String,
+    victory: Option<bool>,
+    difficulty: Option<String>,
+    cycle: Option<String>,
+    set: Option<String>,
+    count: Option<i32>,
+    #[serde(default)]
+    every_scenario: bool,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let contents = std::fs::read_to_string(&args[1])?;
+
+    dotenv::dotenv().ok();
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&std::env::var("DATABASE_URL")?)
+        .await?;
+
+    let doc: Achievements = toml::from_str(&contents)?;
+
+    for achievement in doc.achievement.iter() {
+        let difficulty = achievement
+            .difficulty
+            .as_deref()
+            .map(|difficulty| difficulty.parse::<Difficulty>())
+            .transpose()?;
+        if achievement.every_scenario && achievement.cycle.is_none() && achievement.set.is_none() {
+            anyhow::bail!(
+                "'{}' needs a cycle or set to complete every scenario of",
+                achievement.code
+            );
+        }
+
+        println!("Adding Achievement: {}", &achievement.name);
+        sqlx::query!(
+            r#"
+INSERT INTO achievements ( code, name, description, source, victory, difficulty, cycle, set_name, count, every_scenario )
+VALUES ( $1, $2, $3, $4, $5, $6, $7, $8, $9, $10 )
+ON CONFLICT (code) DO UPDATE
+SET name = EXCLUDED.name,
+    description = EXCLUDED.description,
+    source = EXCLUDED.source,
+    victory = EXCLUDED.victory,
+    difficulty = EXCLUDED.difficulty,
+    cycle = EXCLUDED.cycle,
+    set_name = EXCLUDED.set_name,
+    count = EXCLUDED.count,
+    every_scenario = EXCLUDED.every_scenario,
+    updated_at = CURRENT_TIMESTAMP
+"#,
+            &achievement.code,
+            &achievement.name,
+            &achievement.description,
+            &achievement.source,
+            achievement.victory,
+            difficulty.map(|difficulty| difficulty.as_str()),
+            achievement.cycle,
+            achievement.set,
+            achievement.count.unwrap_or(1),
+            achievement.every_scenario
+        )
+        .execute(&pool)
+        .await?;
+    }
+
+    Ok(())
+}
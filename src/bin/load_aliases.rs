@@ -0,0 +1,45 @@
+use erebor_record_keeper::models::scenario::Scenario;
+use serde::Deserialize;
+use sqlx::postgres::PgPoolOptions;
+
+#[derive(Deserialize)]
+struct Aliases {
+    alias: Vec<Alias>,
+}
+
+#[derive(Deserialize)]
+struct Alias {
+    alias: String,
+    scenario: String,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let contents = std::fs::read_to_string(&args[1])?;
+
+    dotenv::dotenv().ok();
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&std::env::var("DATABASE_URL")?)
+        .await?;
+
+    let doc: Aliases = toml::from_str(&contents).unwrap();
+
+    for alias in doc.alias {
+        // sets are loaded separately, so an alias for a scenario that isn't loaded yet is skipped
+        match Scenario::find_by_title(&pool, &alias.scenario).await? {
+            Some(scenario) => {
+                println!("Adding Alias: {} -> {}", &alias.alias, &scenario.title);
+                scenario.add_alias(&pool, &alias.alias).await?;
+            }
+            None => println!(
+                "Could not find Scenario '{}' for Alias '{}'",
+                alias.scenario, alias.alias
+            ),
+        }
+    }
+
+    Ok(())
+}
@@ -62,6 +62,9 @@ async fn main() -> anyhow::Result<()> {
             r#"
 INSERT INTO challenges ( name, description, code, scenario_id, attributes )
 VALUES ( $1, $2, $3, $4, $5 )
+ON CONFLICT (code) DO UPDATE
+SET attributes = EXCLUDED.attributes,
+    updated_at = CURRENT_TIMESTAMP
 "#,
             &challenge.name,
             &challenge.description,
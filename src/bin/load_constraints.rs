@@ -0,0 +1,55 @@
+use serde::Deserialize;
+use sqlx::postgres::PgPoolOptions;
+
+#[derive(Deserialize)]
+struct Constraints {
+    code_prefix: String,
+    constraint: Vec<Constraint>,
+}
+
+#[derive(Deserialize)]
+struct Constraint {
+    name: String,
+    description: String,
+    category: String,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let contents = std::fs::read_to_string(&args[1])?;
+
+    dotenv::dotenv().ok();
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&std::env::var("DATABASE_URL")?)
+        .await?;
+
+    let doc: Constraints = toml::from_str(&contents).unwrap();
+
+    for (i, constraint) in doc.constraint.iter().enumerate() {
+        let code = format!("{}{:0>2}", doc.code_prefix, i + 1);
+
+        println!("Adding Constraint: {}", &constraint.name);
+        sqlx::query!(
+            r#"
+INSERT INTO deck_constraints ( name, description, code, category )
+VALUES ( $1, $2, $3, $4 )
+ON CONFLICT (code) DO UPDATE
+SET name = EXCLUDED.name,
+    description = EXCLUDED.description,
+    category = EXCLUDED.category,
+    updated_at = CURRENT_TIMESTAMP
+"#,
+            &constraint.name,
+            &constraint.description,
+            &code,
+            &constraint.category
+        )
+        .execute(&pool)
+        .await?;
+    }
+
+    Ok(())
+}
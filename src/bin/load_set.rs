@@ -1,9 +1,12 @@
+use erebor_record_keeper::models::set::ProductType;
 use serde::Deserialize;
 use sqlx::postgres::PgPoolOptions;
 
 #[derive(Deserialize)]
 struct Set {
     name: String,
+    product_type: Option<String>,
+    cycle: Option<String>,
     scenarios: Vec<Scenario>,
 }
 
@@ -11,6 +14,7 @@ struct Set {
 struct Scenario {
     title: String,
     number: i16,
+    difficulty: Option<i16>,
 }
 
 #[tokio::main]
@@ -18,6 +22,11 @@ async fn main() -> anyhow::Result<()> {
     let args: Vec<String> = std::env::args().collect();
     let contents = std::fs::read_to_string(&args[1])?;
     let set: Set = toml::from_str(&contents)?;
+    let product_type = set
+        .product_type
+        .as_deref()
+        .map(|product_type| product_type.parse::<ProductType>())
+        .transpose()?;
 
     dotenv::dotenv().ok();
 
@@ -28,11 +37,13 @@ async fn main() -> anyhow::Result<()> {
 
     let set_id = match sqlx::query!(
         r#"
-INSERT INTO sets ( name )
-VALUES ( $1 )
+INSERT INTO sets ( name, product_type, cycle )
+VALUES ( $1, $2, $3 )
 RETURNING id
 "#,
-        set.name
+        set.name,
+        product_type.map(|product_type| product_type.as_str()),
+        set.cycle
     )
     .fetch_one(&pool)
     .await
@@ -40,14 +51,19 @@ RETURNING id
         Ok(record) => record.id,
         Err(err) => {
             if let sqlx::Error::Database(_) = err {
-                // if the record already exists, fetch the set instead of erroring out
+                // if the record already exists, update the set instead of erroring out
                 sqlx::query!(
                     r#"
-SELECT id
-FROM sets
+UPDATE sets
+SET product_type = COALESCE($2, product_type),
+    cycle = COALESCE($3, cycle),
+    updated_at = CURRENT_TIMESTAMP
 WHERE name = $1
+RETURNING id
 "#,
-                    set.name
+                    set.name,
+                    product_type.map(|product_type| product_type.as_str()),
+                    set.cycle
                 )
                 .fetch_one(&pool)
                 .await?
@@ -62,13 +78,18 @@ WHERE name = $1
         let code = format!("{:0>2}{:0>2}", set_id, scenario.number);
         if let Err(err) = sqlx::query!(
             r#"
-INSERT INTO scenarios ( title, code, set_id, number )
-VALUES ( $1, $2, $3, $4 )
+INSERT INTO scenarios ( title, code, set_id, number, difficulty )
+VALUES ( $1, $2, $3, $4, $5 )
+ON CONFLICT (code)
+DO
+    UPDATE SET difficulty = COALESCE($5, scenarios.difficulty),
+        updated_at = CURRENT_TIMESTAMP
 "#,
             scenario.title,
             code,
             set_id,
-            scenario.number
+            scenario.number,
+            scenario.difficulty
         )
         .execute(&pool)
         .await
@@ -1,3 +1,12 @@
+pub mod campaign;
+pub mod catalog;
 pub mod challenge;
+pub mod collection;
+pub mod completionist;
+pub mod constraint;
 pub mod event;
+pub mod play;
+pub mod profile;
 pub mod quest;
+pub mod score;
+pub mod slash;
@@ -0,0 +1,277 @@
+//! Collection of commands for keeping a saga campaign log
+use crate::{
+    commands::{completionist::parse_result, quest::filter},
+    models::{
+        campaign::{self, Campaign, CampaignResult, LogKind, DEFAULT_CAMPAIGN_CYCLE},
+        scenario::Scenario,
+        user::User,
+    },
+    utils::PostgresPool,
+};
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::channel::Message,
+    prelude::Context,
+};
+
+/// Parse `<code> <win|loss> [penalty:N] [boon:name] [burden:name] [fallen:hero]`
+pub fn parse_campaign_result(input: &str) -> anyhow::Result<(String, CampaignResult)> {
+    let mut tokens = filter::tokenize(input).into_iter();
+    let code = tokens
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("A scenario code or alias is required."))?;
+    let victory = tokens
+        .next()
+        .and_then(parse_result)
+        .ok_or_else(|| anyhow::anyhow!("The result must be either 'win' or 'loss'."))?;
+    let mut result = CampaignResult {
+        victory,
+        ..Default::default()
+    };
+
+    for token in tokens {
+        let (key, value) = token
+            .split_once(':')
+            .filter(|(_, value)| !value.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Unknown campaign entry: {}", token))?;
+        match key.to_lowercase().as_str() {
+            "penalty" => {
+                result.threat_penalty += value
+                    .parse::<i32>()
+                    .map_err(|_| anyhow::anyhow!("The threat penalty must be a number."))?
+            }
+            "boon" => result.boons.push(value.to_string()),
+            "burden" => result.burdens.push(value.to_string()),
+            "fallen" => result.fallen_heroes.push(value.to_string()),
+            _ => return Err(anyhow::anyhow!("Unknown campaign entry: {}", token)),
+        }
+    }
+
+    Ok((code, result))
+}
+
+#[command]
+#[usage = "[saga]"]
+#[example = ""]
+#[example = "The Hobbit"]
+/// Start a new saga campaign, replacing your current one. The saga defaults to The Lord of the
+/// Rings.
+pub async fn start(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let cycle = match args.rest().trim().trim_matches('"') {
+        "" => DEFAULT_CAMPAIGN_CYCLE,
+        cycle => cycle,
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let cycle = match Campaign::find_cycle(pool, cycle).await? {
+        Some(cycle) => cycle,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, format!("No saga found by that name: {}", cycle))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    let campaign = Campaign::create(pool, user.id, &cycle).await?;
+    let scenarios = campaign.find_scenarios(pool).await?;
+    let message = match scenarios.first() {
+        Some(scenario) => format!(
+            "Started a campaign of **{}**. Your first scenario is **{}** (Code: {}).",
+            campaign.cycle, scenario.title, scenario.code
+        ),
+        None => format!("Started a campaign of **{}**.", campaign.cycle),
+    };
+    msg.reply(&ctx.http, message).await?;
+
+    Ok(())
+}
+
+#[command]
+#[num_args(0)]
+#[usage = ""]
+#[example = ""]
+/// Show your campaign log
+pub async fn show(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    let campaign = match Campaign::find_active_by_user(pool, user.id).await? {
+        Some(campaign) => campaign,
+        None => {
+            msg.reply(
+                &ctx.http,
+                "You don't have a campaign. Start one with: !campaign start",
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+    let scenarios = campaign.find_scenarios(pool).await?;
+    let played = campaign.find_played(pool).await?;
+    let log = campaign.find_log(pool).await?;
+    let log_names = |kind: LogKind| {
+        let names = log
+            .iter()
+            .filter(|entry| entry.kind == kind.as_str())
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<&str>>();
+
+        if names.is_empty() {
+            "None".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
+
+    let mut lines = vec![format!("**Campaign Log: {}**", campaign.cycle)];
+    if played.is_empty() {
+        lines.push("No scenarios played yet.".to_string());
+    }
+    for (i, entry) in played.iter().enumerate() {
+        lines.push(format!(
+            "{}.) {} - {}{}",
+            i + 1,
+            entry.title,
+            if entry.victory { "Victory" } else { "Defeat" },
+            if entry.threat_penalty != 0 {
+                format!(" (Threat Penalty: {:+})", entry.threat_penalty)
+            } else {
+                String::new()
+            }
+        ));
+    }
+    lines.push(format!("**Threat Penalty**: {}", campaign.threat_penalty));
+    lines.push(format!("**Boons**: {}", log_names(LogKind::Boon)));
+    lines.push(format!("**Burdens**: {}", log_names(LogKind::Burden)));
+    lines.push(format!(
+        "**Fallen Heroes**: {}",
+        log_names(LogKind::FallenHero)
+    ));
+    lines.push(match campaign::next_scenario(&scenarios, &played) {
+        Some(scenario) => format!(
+            "**Next Scenario**: {} (Code: {})",
+            scenario.title, scenario.code
+        ),
+        None => "The campaign is complete!".to_string(),
+    });
+
+    msg.channel_id.say(&ctx.http, lines.join("\n")).await?;
+
+    Ok(())
+}
+
+#[command]
+#[min_args(2)]
+#[usage = "<code|alias> <win|loss> [penalty:N] [boon:name] [burden:name] [fallen:hero]"]
+#[example = "0401 win boon:\"Mithril Shirt\""]
+#[example = "0402 loss penalty:2 fallen:Boromir burden:\"Grievous Wound\""]
+/// Record the result of the next scenario in your campaign, along with any boons, burdens, fallen
+/// heroes and threat penalty it added to the log. A loss means the scenario has to be replayed.
+pub async fn record(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (code, result) = match parse_campaign_result(args.rest()) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            msg.channel_id.say(&ctx.http, err.to_string()).await?;
+
+            return Ok(());
+        }
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    let campaign = match Campaign::find_active_by_user(pool, user.id).await? {
+        Some(campaign) => campaign,
+        None => {
+            msg.reply(
+                &ctx.http,
+                "You don't have a campaign. Start one with: !campaign start",
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+    let code = match Scenario::find_by_alias(pool, &code).await? {
+        Some(scenario) => scenario.code,
+        None => code,
+    };
+    let scenarios = campaign.find_scenarios(pool).await?;
+    let played = campaign.find_played(pool).await?;
+    let scenario = match campaign::next_scenario(&scenarios, &played) {
+        Some(scenario) if scenario.code.eq_ignore_ascii_case(&code) => scenario,
+        Some(scenario) => {
+            msg.reply(
+                &ctx.http,
+                format!(
+                    "The next scenario in your campaign is **{}** (Code: {}).",
+                    scenario.title, scenario.code
+                ),
+            )
+            .await?;
+
+            return Ok(());
+        }
+        None => {
+            msg.reply(&ctx.http, "Your campaign is already complete.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    campaign.record(pool, scenario.id, &result).await?;
+
+    msg.reply(
+        &ctx.http,
+        format!(
+            "Recorded a {} on **{}**. Your threat penalty is now {}.",
+            if result.victory { "victory" } else { "defeat" },
+            scenario.title,
+            campaign.threat_penalty + result.threat_penalty
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_campaign_results() {
+        assert_eq!(
+            parse_campaign_result(
+                r#"0402 loss penalty:2 fallen:Boromir burden:"Grievous Wound" boon:"Mithril Shirt""#
+            )
+            .unwrap(),
+            (
+                "0402".to_string(),
+                CampaignResult {
+                    victory: false,
+                    threat_penalty: 2,
+                    boons: vec!["Mithril Shirt".to_string()],
+                    burdens: vec!["Grievous Wound".to_string()],
+                    fallen_heroes: vec!["Boromir".to_string()],
+                }
+            )
+        );
+        assert!(parse_campaign_result("0401").is_err());
+        assert!(parse_campaign_result("0401 win penalty:lots").is_err());
+        assert!(parse_campaign_result("0401 win hero:Aragorn").is_err());
+    }
+}
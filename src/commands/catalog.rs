@@ -0,0 +1,135 @@
+//! Collection of commands for browsing the scenario and set catalog
+use crate::{
+    models::{scenario::Scenario, set::Set},
+    utils::{self, PostgresPool},
+};
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::channel::Message,
+    prelude::Context,
+};
+
+#[command("scenario")]
+#[min_args(1)]
+#[usage = "<code|alias|title>"]
+#[example = "0101"]
+#[example = "PtM"]
+#[example = "Passage Through Mirkwood"]
+/// Show a scenario's set, number, code, aliases and the challenges attached to it
+pub async fn scenario_info(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest().trim().trim_matches('"');
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let scenario = match Scenario::find_by_alias_or_code(pool, query).await? {
+        Some(scenario) => Some(scenario),
+        None => Scenario::find_by_title(pool, query).await?,
+    };
+    let scenario = match scenario {
+        Some(scenario) => scenario,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, format!("No scenario found by: {}", query))
+                .await?;
+
+            return Ok(());
+        }
+    };
+    let set = Set::find_by_id(pool, scenario.set_id).await?;
+    let aliases = scenario.find_aliases(pool).await?;
+    let challenges = scenario.find_challenges(pool).await?;
+
+    let mut lines = vec![
+        format!("**{}**", scenario.title),
+        format!("**Set**: {}", set),
+        format!(
+            "**Number**: {}",
+            scenario
+                .number
+                .map(|number| number.to_string())
+                .unwrap_or_else(|| "Unknown".to_string())
+        ),
+        format!("**Code**: {}", scenario.code),
+    ];
+    if let Some(difficulty) = scenario.difficulty {
+        lines.push(format!("**Difficulty**: {}", difficulty));
+    }
+    if !aliases.is_empty() {
+        lines.push(format!("**Aliases**: {}", aliases.join(", ")));
+    }
+    if !challenges.is_empty() {
+        lines.push("**Challenges**:".to_string());
+        lines.extend(challenges.iter().map(|challenge| {
+            format!(
+                "- (Code: **{}**) *{}* - {}",
+                challenge.code,
+                challenge.name,
+                challenge.description.as_deref().unwrap_or("")
+            )
+        }));
+    }
+
+    utils::say_paginated(ctx, msg.channel_id, &lines).await?;
+
+    Ok(())
+}
+
+#[command]
+/// List all the sets in the catalog
+pub async fn sets(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let sets = Set::find_all(pool).await?;
+    if sets.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "There are no sets in the catalog.")
+            .await?;
+
+        return Ok(());
+    }
+    utils::browse_pages(ctx, msg, &utils::number_collection(&sets)).await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[min_args(1)]
+#[usage = "<name>"]
+#[example = "Khazad-dûm"]
+#[example = "Core Set 2"]
+/// List the scenarios in a set
+pub async fn set_info(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let input = args.rest().trim().trim_matches('"');
+    let set = match Set::find_by_name(pool, input).await? {
+        Some(set) => set,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, format!("No set found by that name: {}", input))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let mut lines = vec![format!("**{}**", set)];
+    lines.extend(utils::number_collection(
+        &Scenario::find_by_set(pool, set.id)
+            .await?
+            .into_iter()
+            .map(|scenario| format!("{} (Code: {})", scenario.title, scenario.code))
+            .collect::<Vec<String>>(),
+    ));
+    utils::browse_pages(ctx, msg, &lines).await?;
+
+    Ok(())
+}
@@ -1,45 +1,260 @@
-use crate::utils::PostgresPool;
+use crate::{
+    commands::{
+        completionist::parse_result,
+        profile,
+        quest::{self, draw},
+    },
+    models::{
+        achievement::{AchievementSource, Activity},
+        gauntlet::GauntletRun,
+        user::User,
+    },
+    utils::{self, PostgresPool},
+};
+use gauntlet::{GauntletArgs, Modifier};
 use serenity::{
-    framework::standard::{macros::command, CommandResult},
-    model::channel::Message,
+    framework::standard::{macros::command, Args, CommandResult},
+    model::{channel::Message, user::User as DiscordUser},
     prelude::Context,
 };
+use sqlx::postgres::PgPool;
 
-#[command]
-#[aliases("pain")]
-#[usage = ""]
-#[example = ""]
-/// Display a random gautlet challenge
-pub async fn gauntlet(ctx: &Context, msg: &Message) -> CommandResult {
-    let data = ctx.data.read().await;
-    let pool = data
-        .get::<PostgresPool>()
-        .expect("Expected PostgresPool in TypeMap.");
+pub mod gauntlet;
 
-    let result = sqlx::query!(
+pub const DEFAULT_MODIFIERS_NUM: usize = 1;
+const FAME_SIZE: i64 = 10;
+
+/// Draw a gauntlet and save it for the players until its result is recorded. The first player is
+/// whoever drew it.
+pub async fn build_gauntlet(
+    pool: &PgPool,
+    players: &[&DiscordUser],
+    quantity: usize,
+    gauntlet_args: &GauntletArgs,
+) -> anyhow::Result<String> {
+    let modifiers = sqlx::query!(
         r#"
-SELECT name, description
-FROM challenges 
+SELECT id, name, description, attributes
+FROM challenges
 WHERE 'Gauntlet' = ANY(attributes)
-ORDER BY RANDOM()
-LIMIT 1
+ORDER BY code
 "#,
     )
     .fetch_all(pool)
     .await?
-    .pop();
-
-    let message = if let Some(challenge) = result {
-        format!(
-            "You've selected the following challenge:\n*{}* - {}",
-            challenge.name,
-            challenge.description.unwrap_or("".to_string())
-        )
-    } else {
-        "Could not find any challenges.".to_string()
+    .into_iter()
+    .map(|row| Modifier {
+        id: row.id,
+        name: row.name,
+        description: row.description,
+        attributes: row.attributes.unwrap_or_default(),
+    })
+    .collect();
+
+    let seed = gauntlet_args.options.seed_or(None);
+    let mut rng = draw::seeded_rng(&seed);
+    let modifiers = gauntlet::draw_modifiers(modifiers, quantity, &mut rng);
+    if modifiers.is_empty() {
+        return Ok("Could not find any challenges.".to_string());
+    }
+
+    let mut lines = vec!["You've drawn the following gauntlet:".to_string()];
+    lines.extend(modifiers.iter().map(|modifier| modifier.to_string()));
+    if modifiers.len() < quantity {
+        lines.push(format!(
+            "Only {} modifiers could be combined.",
+            modifiers.len()
+        ));
+    }
+
+    let mut scenario_id = None;
+    if gauntlet_args.quest {
+        if quest::is_unrated_difficulty(pool, &gauntlet_args.filter).await? {
+            return Ok(quest::UNRATED_DIFFICULTY.to_string());
+        }
+
+        let candidates =
+            quest::find_candidates(pool, &gauntlet_args.filter, *players[0].id.as_u64()).await?;
+        match draw::draw(candidates, 1, &gauntlet_args.options, &mut rng).pop() {
+            Some(scenario) => {
+                scenario_id = Some(scenario.id);
+                lines.push(format!(
+                    "**Quest**: {} from {}",
+                    scenario.title, scenario.set_name
+                ));
+            }
+            None => return Ok("Could not find any quests.".to_string()),
+        }
+    }
+    lines.push(format!("Seed: `{}`", seed));
+    lines.push("Record how it went with: !gauntlet result win|loss".to_string());
+
+    let mut users = Vec::new();
+    for player in players {
+        users.push(User::find_or_create(pool, player.id.as_u64(), &player.name).await?);
+    }
+    GauntletRun::create(
+        pool,
+        scenario_id,
+        &seed,
+        &modifiers
+            .iter()
+            .map(|modifier| modifier.id)
+            .collect::<Vec<i64>>(),
+        &users.iter().map(|user| user.id).collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    Ok(lines.join("\n"))
+}
+
+#[command]
+#[usage = "<modifiers=default:1> [quest] [players:@player] [filters]"]
+#[example = ""]
+#[example = "3"]
+#[example = "3 quest players:@Bilbo @Frodo"]
+#[example = "2 quest cycle:dwarrowdelf seed:finals"]
+/// Build a gauntlet out of random modifiers from the Purple Wizard's pool. Add `quest` to pair it
+/// with a random quest, which takes the same filters and options as `quest`. Modifiers that are
+/// mutually exclusive are never drawn together. The gauntlet is saved for you and any `players`
+/// until its result is recorded.
+pub async fn gauntlet(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let quantity = args.single::<usize>().unwrap_or(DEFAULT_MODIFIERS_NUM);
+    let gauntlet_args = match GauntletArgs::parse(args.rest()) {
+        Ok(_) if quantity == 0 => {
+            msg.channel_id
+                .say(&ctx.http, "A gauntlet needs at least one modifier.")
+                .await?;
+
+            return Ok(());
+        }
+        Ok(parsed) => parsed,
+        Err(err) => {
+            msg.channel_id.say(&ctx.http, err.to_string()).await?;
+
+            return Ok(());
+        }
     };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
 
+    let mut players = vec![&msg.author];
+    for player in &gauntlet_args.players {
+        match msg
+            .mentions
+            .iter()
+            .find(|mention| mention.id.as_u64() == player)
+        {
+            Some(mention) => players.push(mention),
+            None => {
+                msg.reply(&ctx.http, "Players must be mentioned.").await?;
+
+                return Ok(());
+            }
+        }
+    }
+
+    let message = build_gauntlet(pool, &players, quantity, &gauntlet_args).await?;
     msg.reply(&ctx.http, message).await?;
 
     Ok(())
 }
+
+#[command]
+#[num_args(1)]
+#[usage = "<win|loss>"]
+#[example = "win"]
+/// Record the result of the latest gauntlet you're attempting
+pub async fn result(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let victory = match args.single::<String>().ok().and_then(parse_result) {
+        Some(victory) => victory,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "The result must be either 'win' or 'loss'.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    let run = match GauntletRun::find_open_by_user(pool, user.id).await? {
+        Some(run) => run,
+        None => {
+            msg.reply(
+                &ctx.http,
+                "You aren't attempting a gauntlet. Draw one with: !gauntlet",
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+    run.close(pool, victory).await?;
+
+    let mut lines = vec![if victory {
+        format!("You survived the gauntlet (seed `{}`)!", run.seed)
+    } else {
+        format!("The gauntlet (seed `{}`) got the better of you.", run.seed)
+    }];
+    let activity = Activity {
+        source: AchievementSource::Gauntlet,
+        scenario_id: None,
+        victory: Some(victory),
+        difficulty: None,
+    };
+    for player in run.find_users(pool).await? {
+        lines.extend(profile::award(pool, &player, &activity).await);
+    }
+    msg.reply(&ctx.http, lines.join("\n")).await?;
+
+    Ok(())
+}
+
+#[command]
+#[num_args(0)]
+#[usage = ""]
+#[example = ""]
+/// Display the hardest gauntlets that have been beaten
+pub async fn fame(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let gauntlets = GauntletRun::find_fame(pool, FAME_SIZE).await?;
+    if gauntlets.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "Nobody has survived a gauntlet yet.")
+            .await?;
+    } else {
+        let mut lines = vec!["Gauntlet Hall of Fame:".to_string()];
+        lines.extend(utils::number_collection(
+            &gauntlets
+                .iter()
+                .map(|gauntlet| {
+                    format!(
+                        "**{} modifiers** by {}{}: {}",
+                        gauntlet.modifiers.len(),
+                        gauntlet.players.join(", "),
+                        gauntlet
+                            .scenario_title
+                            .as_ref()
+                            .map(|title| format!(" on *{}*", title))
+                            .unwrap_or_default(),
+                        gauntlet.modifiers.join(", ")
+                    )
+                })
+                .collect::<Vec<String>>(),
+        ));
+        utils::say_paginated(ctx, msg.channel_id, &lines).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,198 @@
+//! Building gauntlets out of stacked modifiers from the Purple Wizard's pool
+use crate::commands::quest::{
+    self,
+    draw::DrawOptions,
+    filter::{self, ScenarioFilter},
+};
+use rand::{seq::SliceRandom, Rng};
+use serenity::utils::parse_username;
+use std::fmt;
+
+/// Attribute prefix marking modifiers that can't be combined, i.e. `Exclusive: Engagement`.
+/// Modifiers sharing a group are never drawn together.
+pub const EXCLUSIVE_PREFIX: &str = "Exclusive:";
+
+/// A gauntlet challenge that's stacked on top of a quest
+#[derive(Clone, Debug, PartialEq)]
+pub struct Modifier {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub attributes: Vec<String>,
+}
+
+impl Modifier {
+    /// The groups of modifiers this one is mutually exclusive with
+    pub fn exclusive_groups(&self) -> impl Iterator<Item = String> + '_ {
+        self.attributes.iter().filter_map(|attribute| {
+            attribute
+                .strip_prefix(EXCLUSIVE_PREFIX)
+                .map(|group| group.trim().to_lowercase())
+        })
+    }
+
+    /// Both modifiers belong to the same exclusive group
+    pub fn excludes(&self, other: &Modifier) -> bool {
+        self.exclusive_groups()
+            .any(|group| other.exclusive_groups().any(|other| other == group))
+    }
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "*{}* - {}",
+            self.name,
+            self.description.as_deref().unwrap_or("")
+        )
+    }
+}
+
+/// Draw up to `quantity` distinct modifiers, skipping any that are exclusive with one already drawn
+pub fn draw_modifiers<R: Rng + ?Sized>(
+    mut modifiers: Vec<Modifier>,
+    quantity: usize,
+    rng: &mut R,
+) -> Vec<Modifier> {
+    modifiers.shuffle(rng);
+
+    let mut drawn: Vec<Modifier> = Vec::new();
+    for modifier in modifiers {
+        if drawn.len() >= quantity {
+            break;
+        }
+        if !drawn.iter().any(|other| other.excludes(&modifier)) {
+            drawn.push(modifier);
+        }
+    }
+
+    drawn
+}
+
+/// Arguments for building a gauntlet, parsed from `[quest] [players:@player] [filters]`
+#[derive(Debug, Default, PartialEq)]
+pub struct GauntletArgs {
+    /// Pair the modifiers with a quest
+    pub quest: bool,
+    /// Discord ids of the other players attempting the gauntlet
+    pub players: Vec<u64>,
+    pub filter: ScenarioFilter,
+    pub options: DrawOptions,
+}
+
+impl GauntletArgs {
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        let mut tokens = Vec::new();
+        let mut players = Vec::new();
+        // mentions are only players right after `players:`, otherwise they're left to the filters
+        let mut parsing_players = false;
+
+        for token in filter::tokenize(input) {
+            if parsing_players {
+                if let Some(player) = parse_username(&token) {
+                    players.push(player);
+                    continue;
+                }
+            }
+            parsing_players = false;
+
+            if let Some(player) = token
+                .split_once(':')
+                .filter(|(key, _)| key.eq_ignore_ascii_case("players"))
+                .map(|(_, player)| player.to_string())
+            {
+                parsing_players = true;
+                if !player.is_empty() {
+                    players.push(
+                        parse_username(&player)
+                            .ok_or_else(|| anyhow::anyhow!("Not a mention: {}", player))?,
+                    );
+                }
+            } else {
+                tokens.push(token);
+            }
+        }
+
+        Self::from_tokens(false, players, tokens)
+    }
+
+    /// Build the arguments out of the players and the remaining filter and draw option tokens
+    pub fn from_tokens(
+        quest: bool,
+        players: Vec<u64>,
+        tokens: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        let (quest, filter, options) = quest::parse_paired_tokens(quest, tokens, "Gauntlets")?;
+
+        Ok(GauntletArgs {
+            quest,
+            players,
+            filter,
+            options,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn modifier(id: i64, attributes: &[&str]) -> Modifier {
+        Modifier {
+            id,
+            name: format!("Modifier {}", id),
+            description: None,
+            attributes: attributes.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn it_never_combines_exclusive_modifiers() {
+        let modifiers = vec![
+            modifier(1, &["Gauntlet", "Exclusive: Engagement"]),
+            modifier(2, &["Gauntlet", "Exclusive: engagement"]),
+            modifier(3, &["Gauntlet"]),
+            modifier(4, &["Gauntlet", "Exclusive: Threat"]),
+        ];
+
+        for seed in 0..50 {
+            let drawn = draw_modifiers(modifiers.clone(), 4, &mut StdRng::seed_from_u64(seed));
+            let ids: Vec<i64> = drawn.iter().map(|modifier| modifier.id).collect();
+
+            assert_eq!(drawn.len(), 3);
+            assert!(!(ids.contains(&1) && ids.contains(&2)));
+        }
+    }
+
+    #[test]
+    fn it_draws_distinct_modifiers() {
+        let modifiers = (1..=5).map(|id| modifier(id, &["Gauntlet"])).collect();
+        let mut drawn: Vec<i64> = draw_modifiers(modifiers, 3, &mut StdRng::seed_from_u64(7))
+            .iter()
+            .map(|modifier| modifier.id)
+            .collect();
+        drawn.sort_unstable();
+        drawn.dedup();
+
+        assert_eq!(drawn.len(), 3);
+    }
+
+    #[test]
+    fn it_parses_the_gauntlet_args() {
+        let args = GauntletArgs::parse("quest cycle:dwarrowdelf seed:finals").unwrap();
+        assert!(args.quest);
+        assert_eq!(args.filter.cycle.as_deref(), Some("dwarrowdelf"));
+        assert_eq!(args.options.seed.as_deref(), Some("finals"));
+
+        assert!(!GauntletArgs::parse("").unwrap().quest);
+
+        let args = GauntletArgs::parse("players:<@2> <@!3> quest owned:<@4>").unwrap();
+        assert_eq!(args.players, vec![2, 3]);
+        assert_eq!(args.filter.owners, vec![4]);
+        assert!(GauntletArgs::parse("players:bilbo").is_err());
+        assert!(GauntletArgs::parse("cycle:dwarrowdelf").is_err());
+        assert!(GauntletArgs::parse("quest vote").is_err());
+    }
+}
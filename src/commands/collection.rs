@@ -0,0 +1,138 @@
+//! Collection of commands for tracking which sets everyone owns
+use crate::{
+    models::{set::Set, user::User},
+    utils::{self, PostgresPool},
+};
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::channel::Message,
+    prelude::Context,
+};
+
+/// Find the set by the name given, or ask to pick one if there's no name
+async fn find_or_pick_set(
+    ctx: &Context,
+    msg: &Message,
+    args: &Args,
+) -> anyhow::Result<Option<Set>> {
+    let name = args.rest().trim().trim_matches('"');
+    if name.is_empty() {
+        return utils::pick_sets(ctx, msg).await;
+    }
+
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    Set::find_by_name(pool, name).await
+}
+
+#[command]
+#[aliases(add)]
+#[usage = "<set name>"]
+#[example = "Khazad-dûm"]
+#[example = ""]
+/// Add a set to your collection. If no set is given, you'll be asked to pick one.
+pub async fn own(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let set = match find_or_pick_set(ctx, msg, &args).await? {
+        Some(set) => set,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "Could not find that set.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    if set.add_owner(pool, user.id).await? > 0 {
+        msg.reply(
+            &ctx.http,
+            format!("Added **{}** to your collection.", set.name),
+        )
+        .await?;
+    } else {
+        msg.reply(
+            &ctx.http,
+            format!("**{}** is already in your collection.", set.name),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[aliases(remove)]
+#[usage = "<set name>"]
+#[example = "Khazad-dûm"]
+#[example = ""]
+/// Remove a set from your collection. If no set is given, you'll be asked to pick one.
+pub async fn disown(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let set = match find_or_pick_set(ctx, msg, &args).await? {
+        Some(set) => set,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "Could not find that set.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    if set.remove_owner(pool, user.id).await? > 0 {
+        msg.reply(
+            &ctx.http,
+            format!("Removed **{}** from your collection.", set.name),
+        )
+        .await?;
+    } else {
+        msg.reply(
+            &ctx.http,
+            format!("**{}** isn't in your collection.", set.name),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[aliases(list)]
+#[num_args(0)]
+#[usage = ""]
+#[example = ""]
+/// List the sets in your collection
+pub async fn owned(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    let sets = Set::find_by_owner(pool, user.id).await?;
+    if sets.is_empty() {
+        msg.reply(
+            &ctx.http,
+            "Your collection is empty. Add sets with: !collection add <set name>",
+        )
+        .await?;
+    } else {
+        for message in utils::format_large_collection(&sets) {
+            msg.channel_id.say(&ctx.http, message).await?;
+        }
+    }
+
+    Ok(())
+}
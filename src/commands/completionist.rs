@@ -0,0 +1,216 @@
+//! Collection of commands for a personal quest log that lives outside of events
+use crate::{
+    commands::profile,
+    models::{
+        achievement::{AchievementSource, Activity},
+        play::{Difficulty, Play},
+        scenario::Scenario,
+        set::Set,
+        user::User,
+    },
+    utils::{self, PostgresPool},
+};
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::channel::Message,
+    prelude::Context,
+};
+use std::collections::HashMap;
+
+/// Parse a game result into whether it was a victory
+pub fn parse_result(result: impl AsRef<str>) -> Option<bool> {
+    match result.as_ref().to_lowercase().as_str() {
+        "win" | "won" | "victory" => Some(true),
+        "loss" | "lost" | "defeat" => Some(false),
+        _ => None,
+    }
+}
+
+// Struct for rows in completionist
+struct ScenarioRow {
+    title: String,
+    set_id: i64,
+    beaten: Option<bool>,
+}
+
+/// Format a set's scenarios into a single completion line
+pub fn format_set_completion(name: &str, beaten: &[&str], unbeaten: &[&str]) -> String {
+    let total = beaten.len() + unbeaten.len();
+    if unbeaten.is_empty() {
+        format!("**{}** ({}/{}) - Complete!", name, beaten.len(), total)
+    } else {
+        format!(
+            "**{}** ({}/{}) - Remaining: {}",
+            name,
+            beaten.len(),
+            total,
+            unbeaten.join(", ")
+        )
+    }
+}
+
+#[command]
+#[min_args(2)]
+#[usage = "<scenario code|alias> <win|loss>"]
+#[example = "0101 win"]
+#[example = "PtM loss"]
+/// Record a win or loss for any scenario in your personal quest log. Use `log` for more details.
+pub async fn played(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let code = args.single_quoted::<String>()?;
+    let victory = match args.single::<String>().ok().and_then(parse_result) {
+        Some(victory) => victory,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "The result must be either 'win' or 'loss'.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let scenario = match Scenario::find_by_alias_or_code(pool, &code).await? {
+        Some(scenario) => scenario,
+        None => {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!("No scenario found by that code or alias: {}", code),
+                )
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    Play {
+        user_id: user.id,
+        scenario_id: scenario.id,
+        event_id: None,
+        victory,
+        players: None,
+        difficulty: Difficulty::default(),
+        notes: None,
+    }
+    .create(pool)
+    .await?;
+
+    let mut lines = vec![format!(
+        "Logged a {} against **{}**.",
+        if victory { "win" } else { "loss" },
+        scenario.title
+    )];
+    lines.extend(
+        profile::award(
+            pool,
+            &user,
+            &Activity {
+                source: AchievementSource::Play,
+                scenario_id: Some(scenario.id),
+                victory: Some(victory),
+                difficulty: Some(Difficulty::default()),
+            },
+        )
+        .await,
+    );
+    msg.reply(&ctx.http, lines.join("\n")).await?;
+
+    Ok(())
+}
+
+#[command]
+#[num_args(0)]
+#[usage = ""]
+#[example = ""]
+/// Show which sets and scenarios you have and haven't beaten
+pub async fn completionist(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let rows = sqlx::query_as!(
+        ScenarioRow,
+        r#"
+SELECT scenarios.title, scenarios.set_id, (
+    SELECT BOOL_OR(plays.victory)
+    FROM plays, users
+    WHERE plays.scenario_id = scenarios.id
+        AND plays.user_id = users.id
+        AND users.discord_id = $1
+) AS beaten
+FROM scenarios
+ORDER BY scenarios.set_id, scenarios.number, scenarios.code
+"#,
+        *msg.author.id.as_u64() as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut scenarios: HashMap<i64, (Vec<&str>, Vec<&str>)> = HashMap::new();
+    for row in rows.iter() {
+        let (beaten, unbeaten) = scenarios.entry(row.set_id).or_default();
+        if row.beaten.unwrap_or(false) {
+            beaten.push(&row.title);
+        } else {
+            unbeaten.push(&row.title);
+        }
+    }
+
+    let mut beaten_count = 0;
+    let lines: Vec<String> = Set::find_all(pool)
+        .await?
+        .iter()
+        .filter_map(|set| {
+            scenarios.get(&set.id).map(|(beaten, unbeaten)| {
+                beaten_count += beaten.len();
+                format_set_completion(&set.name, beaten, unbeaten)
+            })
+        })
+        .collect();
+
+    msg.reply(
+        &ctx.http,
+        format!(
+            "You've beaten {} of {} scenarios.",
+            beaten_count,
+            rows.len()
+        ),
+    )
+    .await?;
+    utils::browse_pages(ctx, msg, &utils::number_collection(&lines)).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_results() {
+        assert_eq!(parse_result("win"), Some(true));
+        assert_eq!(parse_result("Loss"), Some(false));
+        assert_eq!(parse_result("draw"), None);
+    }
+
+    #[test]
+    fn it_formats_set_completion() {
+        assert_eq!(
+            format_set_completion("Core Set", &["Passage Through Mirkwood"], &[]),
+            "**Core Set** (1/1) - Complete!"
+        );
+        assert_eq!(
+            format_set_completion(
+                "Core Set",
+                &["Passage Through Mirkwood"],
+                &["Journey Along the Anduin", "Escape from Dol Guldur"]
+            ),
+            "**Core Set** (1/3) - Remaining: Journey Along the Anduin, Escape from Dol Guldur"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Collection of commands for drawing random deck-building constraints
+use crate::{
+    commands::quest::{
+        self, draw,
+        draw::DrawOptions,
+        filter::{self, ScenarioFilter},
+    },
+    utils::PostgresPool,
+};
+use rand::{seq::SliceRandom, Rng};
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::channel::Message,
+    prelude::Context,
+};
+use std::fmt;
+
+pub const DEFAULT_CONSTRAINTS_NUM: usize = 2;
+
+/// A deck-building rule. Only one constraint is drawn from each category, so sphere pairs or hero
+/// counts never contradict each other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeckConstraint {
+    pub name: String,
+    pub description: Option<String>,
+    pub category: String,
+}
+
+impl fmt::Display for DeckConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "*{}* - {}",
+            self.name,
+            self.description.as_deref().unwrap_or("")
+        )
+    }
+}
+
+/// Draw up to `quantity` constraints, at most one from each category
+pub fn draw_constraints<R: Rng + ?Sized>(
+    mut constraints: Vec<DeckConstraint>,
+    quantity: usize,
+    rng: &mut R,
+) -> Vec<DeckConstraint> {
+    constraints.shuffle(rng);
+
+    let mut drawn: Vec<DeckConstraint> = Vec::new();
+    for constraint in constraints {
+        if drawn.len() >= quantity {
+            break;
+        }
+        if !drawn
+            .iter()
+            .any(|other| other.category.eq_ignore_ascii_case(&constraint.category))
+        {
+            drawn.push(constraint);
+        }
+    }
+
+    drawn
+}
+
+/// Parse `[quest] [filters]`, returning whether to pair the constraints with a quest
+pub fn parse_constraint_args(input: &str) -> anyhow::Result<(bool, ScenarioFilter, DrawOptions)> {
+    quest::parse_paired_tokens(false, filter::tokenize(input), "Constraints")
+}
+
+#[command]
+#[usage = "<quantity=default:2> [quest] [filters]"]
+#[example = ""]
+#[example = "3"]
+#[example = "2 quest cycle:dwarrowdelf"]
+#[example = "3 seed:finals"]
+/// Draw random deck-building constraints, at most one from each category. Add `quest` to pair them
+/// with a random quest, which takes the same filters and options as `quest`.
+pub async fn constraints(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let quantity = args.single::<usize>().unwrap_or(DEFAULT_CONSTRAINTS_NUM);
+    let (with_quest, filter, options) = match parse_constraint_args(args.rest()) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            msg.channel_id.say(&ctx.http, err.to_string()).await?;
+
+            return Ok(());
+        }
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let constraints = sqlx::query_as!(
+        DeckConstraint,
+        r#"
+SELECT name, description, category
+FROM deck_constraints
+ORDER BY code
+"#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let seed = options.seed_or(None);
+    let mut rng = draw::seeded_rng(&seed);
+    let constraints = draw_constraints(constraints, quantity, &mut rng);
+    if constraints.is_empty() {
+        msg.reply(&ctx.http, "Could not find any constraints.")
+            .await?;
+
+        return Ok(());
+    }
+
+    let mut lines = vec!["Build your decks with these constraints:".to_string()];
+    lines.extend(constraints.iter().map(|constraint| constraint.to_string()));
+    if with_quest {
+        if quest::is_unrated_difficulty(pool, &filter).await? {
+            msg.reply(&ctx.http, quest::UNRATED_DIFFICULTY).await?;
+
+            return Ok(());
+        }
+
+        let candidates = quest::find_candidates(pool, &filter, *msg.author.id.as_u64()).await?;
+        match draw::draw(candidates, 1, &options, &mut rng).pop() {
+            Some(scenario) => lines.push(format!(
+                "**Quest**: {} from {}",
+                scenario.title, scenario.set_name
+            )),
+            None => {
+                msg.reply(&ctx.http, "Could not find any quests.").await?;
+
+                return Ok(());
+            }
+        }
+    }
+    lines.push(format!("Seed: `{}`", seed));
+
+    msg.reply(&ctx.http, lines.join("\n")).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn constraint(name: &str, category: &str) -> DeckConstraint {
+        DeckConstraint {
+            name: name.to_string(),
+            description: None,
+            category: category.to_string(),
+        }
+    }
+
+    #[test]
+    fn it_draws_one_constraint_per_category() {
+        let constraints = vec![
+            constraint("Leadership and Lore", "Spheres"),
+            constraint("Mono Tactics", "Spheres"),
+            constraint("Dwarf Only", "Traits"),
+            constraint("No Allies", "Card Types"),
+        ];
+
+        for seed in 0..50 {
+            let mut categories: Vec<String> =
+                draw_constraints(constraints.clone(), 4, &mut StdRng::seed_from_u64(seed))
+                    .into_iter()
+                    .map(|constraint| constraint.category)
+                    .collect();
+            categories.sort_unstable();
+
+            assert_eq!(categories, vec!["Card Types", "Spheres", "Traits"]);
+        }
+    }
+
+    #[test]
+    fn it_parses_the_constraint_args() {
+        let (with_quest, filter, options) =
+            parse_constraint_args("quest exclude:saga seed:finals").unwrap();
+        assert!(with_quest);
+        assert_eq!(filter.exclude.len(), 1);
+        assert_eq!(options.seed.as_deref(), Some("finals"));
+
+        assert!(!parse_constraint_args("").unwrap().0);
+        assert!(parse_constraint_args("exclude:saga").is_err());
+    }
+}
@@ -1,19 +1,28 @@
 use crate::{
-    models::{event::Event, user::User},
+    commands::profile,
+    models::{
+        achievement::{AchievementSource, Activity},
+        event::{Event, EventChallenge},
+        user::User,
+    },
     utils::{self, PostgresPool},
 };
 use serenity::{
     framework::standard::{macros::command, Args, CommandResult},
-    model::channel::Message,
+    model::{channel::Message, user::User as DiscordUser},
     prelude::Context,
     utils::MessageBuilder,
 };
+use sqlx::postgres::PgPool;
 use std::collections::HashMap;
 
 pub mod admin;
 pub mod group_challenges;
 pub mod group_scenarios;
 pub mod kang;
+pub mod milestones;
+pub mod rewards;
+pub mod status;
 
 // Struct for rows in cquest
 #[derive(Debug)]
@@ -137,7 +146,7 @@ WHERE challenges_events.event_id = $1
                 "- (Code: **{}**) *{}* - {}\n",
                 challenge.code,
                 challenge.name,
-                challenge.description.unwrap_or_else(|| "")
+                challenge.description.unwrap_or("")
             ));
         }
     }
@@ -215,7 +224,7 @@ WHERE events_scenarios.event_id = $1
                 "- (Code: **{}**) *{}* - {}\n",
                 challenge.code,
                 challenge.name,
-                challenge.description.unwrap_or_else(|| "")
+                challenge.description.unwrap_or("")
             ));
         }
 
@@ -225,81 +234,24 @@ WHERE events_scenarios.event_id = $1
     Ok(())
 }
 
-#[command]
-#[min_args(1)]
-#[aliases("complete")]
-#[usage = "<challenge code>"]
-#[example = "CON1901"]
-/// Mark a challenge as complete
-pub async fn ccomplete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let code = match args.single::<String>() {
-        Ok(code) => code,
-        Err(_) => {
-            utils::check_msg(
-                msg.channel_id
-                    .say(
-                        &ctx.http,
-                        "Requires an argument: !event ccomplete <challenge code>",
-                    )
-                    .await,
-            );
-
-            return Ok(());
-        }
-    };
-    let data = ctx.data.read().await;
-    let pool = data
-        .get::<PostgresPool>()
-        .expect("Expected PostgresPool in TypeMap.");
-
-    let event = match Event::find_by_active(&pool, true).await? {
-        Some(event) => event,
-        None => {
-            utils::check_msg(
-                msg.channel_id
-                    .say(&ctx.http, "There is no active event.")
-                    .await,
-            );
-
-            return Ok(());
-        }
-    };
-    let challenge_event = sqlx::query!(
-        r#"
-SELECT challenges_events.id, challenges.name
-FROM challenges, challenges_events, events
-WHERE challenges.code = $1
-    AND events.id = $2
-    AND challenges_events.challenge_id = challenges.id
-    AND challenges_events.event_id = events.id
-    "#,
-        code,
-        event.id
-    )
-    .fetch_all(pool)
-    .await?
-    .pop();
-
-    let challenge_event = match challenge_event {
-        Some(challenge_event) => challenge_event,
-        None => {
-            utils::check_msg(
-                msg.channel_id
-                    .say(&ctx.http, "Could not find a challenge by that code.")
-                    .await,
-            );
-
-            return Ok(());
-        }
-    };
-
+/// Record the challenge as complete for each of the players, mentioning those who hadn't
+/// completed it yet
+pub async fn complete_challenge(
+    pool: &PgPool,
+    challenge_event: &EventChallenge,
+    players: &[&DiscordUser],
+) -> anyhow::Result<String> {
     let mut reply = MessageBuilder::new();
-    let mut mentioned_users: Vec<&serenity::model::user::User> =
-        msg.mentions.iter().map(|u| u).collect();
-    mentioned_users.insert(0, &msg.author);
-    let mut completed_users: Vec<Option<&serenity::model::user::User>> = Vec::new();
+    let mut completed_users: Vec<&DiscordUser> = Vec::new();
+    let mut earned: Vec<String> = Vec::new();
+    let activity = Activity {
+        source: AchievementSource::Challenge,
+        scenario_id: None,
+        victory: Some(true),
+        difficulty: None,
+    };
 
-    for discord_user in mentioned_users.iter() {
+    for discord_user in players.iter() {
         let user = User::find_or_create(pool, discord_user.id.as_u64(), &discord_user.name).await?;
         let row_count = sqlx::query!(
             r#"
@@ -307,26 +259,21 @@ INSERT INTO challenges_events_users ( challenges_events_id, user_id )
 VALUES ( $1, $2 )
 ON CONFLICT DO NOTHING
 "#,
-            challenge_event.id,
+            challenge_event.challenge_event_id,
             user.id
         )
         .execute(pool)
         .await?
         .rows_affected();
 
-        completed_users.push(if row_count > 0 {
-            Some(discord_user)
-        } else {
-            None
-        });
+        if row_count > 0 {
+            completed_users.push(discord_user);
+            earned.extend(profile::award(pool, &user, &activity).await);
+        }
     }
 
-    let completed_users: Vec<_> = completed_users
-        .into_iter()
-        .filter_map(|user| user)
-        .collect();
     if completed_users.is_empty() {
-        for discord_user in mentioned_users.iter() {
+        for discord_user in players.iter() {
             reply.mention(*discord_user);
             reply.push(" ");
         }
@@ -344,8 +291,85 @@ ON CONFLICT DO NOTHING
             challenge_event.name
         ));
     }
+    for line in earned {
+        reply.push("\n");
+        reply.push(line);
+    }
+
+    Ok(reply.build())
+}
+
+#[command]
+#[min_args(1)]
+#[aliases("complete")]
+#[usage = "<challenge code|name>"]
+#[example = "CON1901"]
+#[example = "cut off @player"]
+/// Mark a challenge as complete. The challenge can be given by its code or name.
+pub async fn ccomplete(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = utils::strip_mentions(args.rest());
+    if query.is_empty() {
+        utils::check_msg(
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    "Requires an argument: !event ccomplete <challenge code|name>",
+                )
+                .await,
+        );
+
+        return Ok(());
+    }
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let event = match Event::find_by_active(&pool, true).await? {
+        Some(event) => event,
+        None => {
+            utils::check_msg(
+                msg.channel_id
+                    .say(&ctx.http, "There is no active event.")
+                    .await,
+            );
+
+            return Ok(());
+        }
+    };
+    let challenges = event.find_challenges(pool, None).await?;
+    let challenge_event = match utils::resolve(
+        ctx,
+        msg,
+        &query,
+        &challenges,
+        |challenge| &challenge.code,
+        |challenge| &challenge.name,
+    )
+    .await?
+    {
+        Some(challenge_event) => challenge_event,
+        None => {
+            utils::check_msg(
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        "Could not find a challenge by that code or name.",
+                    )
+                    .await,
+            );
+
+            return Ok(());
+        }
+    };
+
+    let mut players: Vec<&DiscordUser> = vec![&msg.author];
+    players.extend(msg.mentions.iter());
+    let reply = complete_challenge(pool, challenge_event, &players).await?;
 
-    utils::check_msg(msg.channel_id.say(&ctx.http, &reply.build()).await);
+    utils::check_msg(msg.channel_id.say(&ctx.http, reply).await);
+    milestones::announce(ctx, pool, &event, msg.channel_id).await;
+    rewards::grant(ctx, pool, &event, msg.guild_id, msg.channel_id, &players).await;
 
     Ok(())
 }
@@ -1,14 +1,38 @@
-use crate::{models::event::Event, utils, utils::PostgresPool};
+use super::status;
+use crate::{
+    commands::quest::{self, filter::ScenarioFilter},
+    models::{
+        event::Event,
+        guild::GuildSettings,
+        milestone::{Milestone, MilestoneKind},
+        reward::{Reward, RewardRule},
+        scenario::Scenario,
+        set::Set,
+    },
+    utils,
+    utils::PostgresPool,
+};
 use serenity::{
     framework::standard::{macros::command, Args, CommandResult},
     model::channel::Message,
     prelude::Context,
+    utils::parse_role,
 };
-use std::time::Duration;
 
 #[command]
-/// Add scenarios to an event
-pub async fn add(ctx: &Context, msg: &Message) -> CommandResult {
+#[usage = "[filters]"]
+#[example = ""]
+#[example = "cycle:dwarrowdelf"]
+/// Add scenarios to an event. If filters are given, every scenario matching them is added.
+pub async fn add(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let filter = match ScenarioFilter::parse(args.rest()) {
+        Ok(filter) => filter,
+        Err(err) => {
+            msg.channel_id.say(&ctx.http, err.to_string()).await?;
+
+            return Ok(());
+        }
+    };
     let data = ctx.data.read().await;
     let pool = data
         .get::<PostgresPool>()
@@ -26,59 +50,83 @@ pub async fn add(ctx: &Context, msg: &Message) -> CommandResult {
         return Ok(());
     }
 
-    msg.channel_id
-        .say(
-            &ctx.http,
-            utils::format_collection(&events.iter().map(|event| &event.name).collect()),
-        )
-        .await?;
-    let event;
-    if let Some(e) = utils::pick_collection(ctx, msg, &events).await? {
-        event = e;
-    } else {
-        return Ok(());
-    }
+    let event = match utils::pick(ctx, msg, "Which event?", &events, |event| {
+        event.name.clone()
+    })
+    .await?
+    {
+        Some(event) => event,
+        None => return Ok(()),
+    };
 
-    msg.channel_id
-        .say(
-            &ctx.http,
-            r#"Do you want to add individual scenarios or by set?
-1.) Scenarios
-2.) Set
-3.) All
+    if !filter.is_empty() {
+        if quest::is_unrated_difficulty(pool, &filter).await? {
+            msg.channel_id
+                .say(&ctx.http, quest::UNRATED_DIFFICULTY)
+                .await?;
+
+            return Ok(());
+        }
+
+        let scenario_ids = quest::find_candidates(pool, &filter, *msg.author.id.as_u64())
+            .await?
+            .into_iter()
+            .map(|(scenario, _)| scenario.id)
+            .collect::<Vec<i64>>();
+        let row_count = sqlx::query!(
+            r#"
+INSERT INTO events_scenarios ( event_id, scenario_id )
+SELECT $1, candidates.scenario_id
+FROM UNNEST($2::BIGINT[]) AS candidates(scenario_id)
+WHERE candidates.scenario_id NOT IN (
+    SELECT scenario_id
+    FROM events_scenarios
+    WHERE event_id = $1
+)
 "#,
+            event.id,
+            &scenario_ids,
         )
-        .await?;
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        msg.channel_id
+            .say(&ctx.http, format!("{} scenarios added.", row_count))
+            .await?;
+
+        return Ok(());
+    }
 
-    if let Some(choice) = &msg
-        .author
-        .await_reply(&ctx)
-        .timeout(Duration::from_secs(utils::SELECTION_TIMEOUT))
-        .await
+    let choices = ["Scenarios", "Set", "All"];
+    if let Some(choice) = utils::pick(
+        ctx,
+        msg,
+        "Do you want to add individual scenarios or by set?",
+        &choices,
+        |choice| choice.to_string(),
+    )
+    .await?
     {
-        if choice.content == "1" {
-            if let Some(set_id) = utils::pick_sets(ctx, msg).await? {
+        if *choice == "Scenarios" {
+            if let Some(set) = utils::pick_sets(ctx, msg).await? {
                 let scenarios = sqlx::query!(
                     r#"
 SELECT id, title
 FROM scenarios
 WHERE scenarios.set_id = $1
 "#,
-                    set_id
+                    set.id
                 )
                 .fetch_all(pool)
                 .await?;
 
-                msg.channel_id
-                    .say(
-                        &ctx.http,
-                        utils::format_collection(
-                            &scenarios.iter().map(|scenario| &scenario.title).collect(),
-                        ),
-                    )
-                    .await?;
-
-                if let Some(scenario) = utils::pick_collection(ctx, msg, &scenarios).await? {
+                if let Some(scenario) =
+                    utils::pick(ctx, msg, "Which scenario?", &scenarios, |scenario| {
+                        scenario.title.clone()
+                    })
+                    .await?
+                {
                     let row_count = sqlx::query!(
                         r#"
 INSERT INTO events_scenarios ( event_id, scenario_id )
@@ -94,14 +142,10 @@ VALUES ( $1, $2 )
                     msg.channel_id
                         .say(&ctx.http, format!("{} scenarios added.", row_count))
                         .await?;
-                } else {
-                    msg.channel_id.say(&ctx.http, "Not a valid choice.").await?;
                 }
-            } else {
-                msg.channel_id.say(&ctx.http, "Not a valid index.").await?;
             }
-        } else if choice.content == "2" {
-            if let Some(set_id) = utils::pick_sets(ctx, msg).await? {
+        } else if *choice == "Set" {
+            if let Some(set) = utils::pick_sets(ctx, msg).await? {
                 let row_count = sqlx::query!(
                     r#"
 INSERT INTO events_scenarios ( event_id, scenario_id )
@@ -115,7 +159,7 @@ WHERE scenarios.set_id = $2
   )
 "#,
                     event.id,
-                    set_id
+                    set.id
                 )
                 .execute(pool)
                 .await?
@@ -124,10 +168,8 @@ WHERE scenarios.set_id = $2
                 msg.channel_id
                     .say(&ctx.http, format!("{} scenarios added.", row_count))
                     .await?;
-            } else {
-                msg.channel_id.say(&ctx.http, "Not a valid index.").await?;
             }
-        } else if choice.content == "3" {
+        } else if *choice == "All" {
             let row_count = sqlx::query!(
                 r#"
 INSERT INTO events_scenarios ( event_id, scenario_id )
@@ -173,14 +215,11 @@ WHERE active = false
     .fetch_all(pool)
     .await?;
 
-    msg.channel_id
-        .say(
-            &ctx.http,
-            utils::format_collection(&events.iter().map(|event| &event.name).collect()),
-        )
-        .await?;
-
-    if let Some(event) = utils::pick_collection(ctx, msg, &events).await? {
+    if let Some(event) = utils::pick(ctx, msg, "Which event?", &events, |event| {
+        event.name.clone()
+    })
+    .await?
+    {
         sqlx::query!(
             r#"
 UPDATE events
@@ -215,33 +254,522 @@ WHERE id = $1"#,
 }
 
 #[command]
-/// Archive an event
-pub async fn archive(ctx: &Context, msg: &Message) -> CommandResult {
+#[usage = "[seed]"]
+#[example = "finals"]
+#[example = ""]
+/// Set the seed the active event draws its quests with. Leave it empty to clear the seed.
+pub async fn seed(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let seed = args.rest().trim();
     let data = ctx.data.read().await;
     let pool = data
         .get::<PostgresPool>()
         .expect("Expected PostgresPool in TypeMap.");
 
-    let events = Event::find_by_archive(pool, false).await?;
-    if events.is_empty() {
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "No active event found.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    if seed.is_empty() {
+        event.update_seed(pool, None).await?;
+        msg.channel_id
+            .say(&ctx.http, format!("Cleared the seed for '{}'.", event.name))
+            .await?;
+    } else {
+        event.update_seed(pool, Some(seed)).await?;
         msg.channel_id
             .say(
                 &ctx.http,
-                "There are no unarchived events. Please create one.",
+                format!("'{}' now draws quests with seed `{}`.", event.name, seed),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[num_args(0)]
+#[usage = ""]
+#[example = ""]
+/// Post the active event's status in this channel and pin it. It keeps itself up to date as quests
+/// are completed and checked out, replacing any status posted before.
+pub async fn status(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "No active event found.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    status::post(ctx, pool, &event, msg.channel_id).await?;
+
+    Ok(())
+}
+
+/// Parse a hex color like `#2e8b57`
+fn parse_color(color: &str) -> Option<i32> {
+    i32::from_str_radix(color.trim_start_matches('#'), 16)
+        .ok()
+        .filter(|color| (0..=0xFFFFFF).contains(color))
+}
+
+#[command]
+#[usage = "[color] [thumbnail url]"]
+#[example = "#2e8b57 https://example.com/kang.png"]
+#[example = "#2e8b57"]
+#[example = ""]
+/// Set the color and thumbnail of the active event's embeds. Leave them out to go back to the
+/// default look.
+pub async fn style(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let color = match args.single::<String>().ok() {
+        Some(color) => match parse_color(&color) {
+            Some(color) => Some(color),
+            None => {
+                msg.channel_id
+                    .say(&ctx.http, "The color must be in hex, like #2e8b57.")
+                    .await?;
+
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    let thumbnail_url = Some(args.rest().trim()).filter(|url| !url.is_empty());
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "No active event found.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    event.update_style(pool, color, thumbnail_url).await?;
+    msg.channel_id
+        .say(&ctx.http, format!("Updated the look of '{}'.", event.name))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[num_args(1)]
+#[usage = "<on|off>"]
+#[example = "off"]
+/// Turn embeds on or off for this server. With them off, replies are sent as plain text.
+pub async fn embeds(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "Embeds can only be turned off in a server.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+    let plain_text = match args.single::<String>()?.to_lowercase().as_str() {
+        "on" => false,
+        "off" => true,
+        _ => {
+            msg.channel_id
+                .say(&ctx.http, "Embeds can be either 'on' or 'off'.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    GuildSettings::update_plain_text(pool, guild_id.0, plain_text).await?;
+    msg.channel_id
+        .say(
+            &ctx.http,
+            if plain_text {
+                "Replies will be sent as plain text."
+            } else {
+                "Replies will be sent as embeds."
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Parse the percentages of a kind of milestone and the role to ping, in any order. `clear` gives
+/// no milestones at all.
+fn parse_milestones(tokens: &[&str]) -> Result<(Vec<i16>, Option<u64>), String> {
+    if tokens == ["clear"] {
+        return Ok((Vec::new(), None));
+    }
+
+    let mut percents = Vec::new();
+    let mut role_id = None;
+    for token in tokens {
+        if let Some(role) = parse_role(token) {
+            role_id = Some(role);
+            continue;
+        }
+        match token.trim_end_matches('%').parse::<i16>() {
+            Ok(percent) if (1..=100).contains(&percent) => percents.push(percent),
+            _ => {
+                return Err(format!(
+                    "'{}' isn't a percentage from 1 to 100 or a role.",
+                    token
+                ))
+            }
+        }
+    }
+    if percents.is_empty() {
+        return Err("Give at least one percentage, or 'clear'.".to_string());
+    }
+    percents.sort_unstable();
+    percents.dedup();
+
+    Ok((percents, role_id))
+}
+
+fn format_milestones(milestones: &[Milestone]) -> String {
+    if milestones.is_empty() {
+        return "The event has no milestones.".to_string();
+    }
+
+    milestones
+        .iter()
+        .map(|milestone| {
+            format!(
+                "{} {}%{}{}",
+                milestone.kind,
+                milestone.percent,
+                milestone
+                    .role_id
+                    .map(|role_id| format!(", pinging <@&{}>", role_id))
+                    .unwrap_or_default(),
+                if milestone.announced {
+                    " (announced)"
+                } else {
+                    ""
+                }
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[command]
+#[usage = "[quests|challenges|kangs] [percentages|clear] [@role]"]
+#[example = "quests 25 50 75 100 @Fellowship"]
+#[example = "kangs clear"]
+#[example = ""]
+/// Set the progress milestones announced for the active event, replacing those of the kind.
+/// Each is announced once, in the channel where it's reached, pinging the role if one is given.
+/// Leave everything out to list them.
+pub async fn milestones(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let kind = match args.single::<String>().ok() {
+        Some(kind) => match MilestoneKind::parse(&kind) {
+            Some(kind) => Some(kind),
+            None => {
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        "Milestones can be for 'quests', 'challenges' or 'kangs'.",
+                    )
+                    .await?;
+
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "No active event found.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let kind = match kind {
+        Some(kind) => kind,
+        None => {
+            let milestones = Milestone::find_by_event(pool, event.id).await?;
+            // list roles without pinging everyone who has them
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.content(format_milestones(&milestones))
+                        .allowed_mentions(|am| am.empty_parse())
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
+    let tokens: Vec<&str> = args.rest().split_whitespace().collect();
+    let (percents, role_id) = match parse_milestones(&tokens) {
+        Ok(parsed) => parsed,
+        Err(reply) => {
+            msg.channel_id.say(&ctx.http, reply).await?;
+
+            return Ok(());
+        }
+    };
+
+    Milestone::replace(pool, event.id, kind, &percents, role_id).await?;
+    msg.channel_id
+        .say(
+            &ctx.http,
+            if percents.is_empty() {
+                format!("Cleared the {} milestones of '{}'.", kind, event.name)
+            } else {
+                format!("Updated the {} milestones of '{}'.", kind, event.name)
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// What to do with a role handed out as a reward
+#[derive(Debug, PartialEq)]
+enum RewardChange<'a> {
+    /// Give it for completing this many challenges
+    Challenges(i32),
+    /// Give it for completing every scenario of a set, by name or picked from a menu
+    Set(Option<&'a str>),
+    Clear,
+}
+
+fn parse_reward(args: &str) -> Result<(u64, RewardChange<'_>), String> {
+    let usage = "Usage: !event reward <@role> <challenges <count>|set [name]|clear>";
+    let mut tokens = args.trim().splitn(3, char::is_whitespace);
+    let role_id = tokens
+        .next()
+        .and_then(parse_role)
+        .ok_or_else(|| usage.to_string())?;
+    let change = match tokens.next().map(|kind| kind.to_lowercase()).as_deref() {
+        Some("challenges") => match tokens.next().map(|count| count.trim().parse::<i32>()) {
+            Some(Ok(count)) if count > 0 => RewardChange::Challenges(count),
+            _ => return Err("The number of challenges must be at least 1.".to_string()),
+        },
+        Some("set") => {
+            RewardChange::Set(tokens.next().map(str::trim).filter(|name| !name.is_empty()))
+        }
+        Some("clear") => RewardChange::Clear,
+        _ => return Err(usage.to_string()),
+    };
+
+    Ok((role_id, change))
+}
+
+fn format_rewards(rewards: &[Reward]) -> String {
+    if rewards.is_empty() {
+        return "The event has no rewards.".to_string();
+    }
+
+    rewards
+        .iter()
+        .map(|reward| {
+            format!(
+                "<@&{}> for {}, given to {} players",
+                reward.role_id,
+                match (reward.challenges, &reward.set_name) {
+                    (Some(challenges), _) => format!("completing {} challenges", challenges),
+                    (None, Some(set_name)) => format!("completing every quest of {}", set_name),
+                    (None, None) => "nothing".to_string(),
+                },
+                reward.granted
             )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[command]
+#[usage = "[@role] [challenges <count>|set [name]|clear]"]
+#[example = "@Ring-bearer challenges 10"]
+#[example = "@Dwarf-friend set Khazad-dûm"]
+#[example = "@Ring-bearer clear"]
+#[example = ""]
+/// Give players a role once they complete enough of the active event's challenges, or every quest
+/// it has from a set. Leave out the set's name to pick it from a menu, or everything to list the
+/// event's rewards.
+pub async fn reward(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "No active event found.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    if args.rest().trim().is_empty() {
+        let rewards = Reward::find_by_event(pool, event.id).await?;
+        // list roles without pinging everyone who has them
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.content(format_rewards(&rewards))
+                    .allowed_mentions(|am| am.empty_parse())
+            })
             .await?;
 
         return Ok(());
     }
+    let (role_id, change) = match parse_reward(args.rest()) {
+        Ok(parsed) => parsed,
+        Err(reply) => {
+            msg.channel_id.say(&ctx.http, reply).await?;
 
+            return Ok(());
+        }
+    };
+
+    let rule = match change {
+        RewardChange::Challenges(count) => RewardRule::Challenges(count),
+        RewardChange::Set(Some(name)) => match Set::find_by_name(pool, name).await? {
+            Some(set) => RewardRule::Set(set.id),
+            None => {
+                msg.channel_id
+                    .say(&ctx.http, format!("No set found by: {}", name))
+                    .await?;
+
+                return Ok(());
+            }
+        },
+        RewardChange::Set(None) => match utils::pick_sets(ctx, msg).await? {
+            Some(set) => RewardRule::Set(set.id),
+            None => return Ok(()),
+        },
+        RewardChange::Clear => {
+            let reply = if Reward::delete(pool, event.id, role_id).await? > 0 {
+                "The role is no longer a reward."
+            } else {
+                "The role isn't a reward."
+            };
+            msg.channel_id.say(&ctx.http, reply).await?;
+
+            return Ok(());
+        }
+    };
+
+    Reward::replace(pool, event.id, role_id, &rule).await?;
     msg.channel_id
         .say(
             &ctx.http,
-            utils::format_collection(&events.iter().map(|event| &event.name).collect()),
+            format!("Updated the role's reward in '{}'.", event.name),
         )
         .await?;
 
-    if let Some(event) = utils::pick_collection(ctx, msg, &events).await? {
+    Ok(())
+}
+
+#[command]
+#[min_args(2)]
+#[usage = "<alias> <scenario code|title>"]
+#[example = "PtM 0101"]
+#[example = "\"Carn Dum\" Carn Dûm"]
+/// Add an alias for a scenario, i.e. a community abbreviation. An alias that's already taken is
+/// moved over to the new scenario.
+pub async fn alias(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let alias = args.single_quoted::<String>()?;
+    let query = args.rest().trim().trim_matches('"');
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let scenario = match Scenario::find_by_code(pool, query).await? {
+        Some(scenario) => Some(scenario),
+        None => Scenario::find_by_title(pool, query).await?,
+    };
+    let scenario = match scenario {
+        Some(scenario) => scenario,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, format!("No scenario found by: {}", query))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    scenario.add_alias(pool, &alias).await?;
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("'{}' is now an alias for {}.", alias, scenario.title),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+/// Archive an event
+pub async fn archive(ctx: &Context, msg: &Message) -> CommandResult {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let events = Event::find_by_archive(pool, false).await?;
+    if events.is_empty() {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                "There are no unarchived events. Please create one.",
+            )
+            .await?;
+
+        return Ok(());
+    }
+
+    if let Some(event) = utils::pick(ctx, msg, "Which event?", &events, |event| {
+        event.name.clone()
+    })
+    .await?
+    {
         // make any active event inactive when archiving
         sqlx::query!(
             r#"
@@ -259,10 +787,6 @@ WHERE id = $1
         msg.channel_id
             .say(&ctx.http, format!("'{}' is now archived.", event.name))
             .await?;
-    } else {
-        msg.channel_id
-            .say(&ctx.http, "Could not archive event.")
-            .await?;
     }
 
     Ok(())
@@ -287,13 +811,11 @@ pub async fn cload(ctx: &Context, msg: &Message) -> CommandResult {
 
         return Ok(());
     }
-    msg.channel_id
-        .say(
-            &ctx.http,
-            utils::format_collection(&events.iter().map(|event| &event.name).collect()),
-        )
-        .await?;
-    let event = match utils::pick_collection(ctx, msg, &events).await? {
+    let event = match utils::pick(ctx, msg, "Which event?", &events, |event| {
+        event.name.clone()
+    })
+    .await?
+    {
         Some(event) => event,
         None => return Ok(()),
     };
@@ -357,3 +879,48 @@ pub async fn create(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_colors() {
+        assert_eq!(parse_color("#2e8b57"), Some(0x2e8b57));
+        assert_eq!(parse_color("FFFFFF"), Some(0xFFFFFF));
+        assert_eq!(parse_color("#1000000"), None);
+        assert_eq!(parse_color("green"), None);
+    }
+
+    #[test]
+    fn it_parses_milestones() {
+        assert_eq!(
+            parse_milestones(&["75", "25%", "<@&42>", "50", "25"]),
+            Ok((vec![25, 50, 75], Some(42)))
+        );
+        assert_eq!(parse_milestones(&["clear"]), Ok((Vec::new(), None)));
+        assert!(parse_milestones(&["<@&42>"]).is_err());
+        assert!(parse_milestones(&["150"]).is_err());
+        assert!(parse_milestones(&["half"]).is_err());
+    }
+
+    #[test]
+    fn it_parses_rewards() {
+        assert_eq!(
+            parse_reward("<@&42> challenges 10"),
+            Ok((42, RewardChange::Challenges(10)))
+        );
+        assert_eq!(
+            parse_reward("<@&42> set The Grey Havens"),
+            Ok((42, RewardChange::Set(Some("The Grey Havens"))))
+        );
+        assert_eq!(
+            parse_reward("<@&42> SET"),
+            Ok((42, RewardChange::Set(None)))
+        );
+        assert_eq!(parse_reward("<@&42> clear"), Ok((42, RewardChange::Clear)));
+        assert!(parse_reward("<@&42> challenges 0").is_err());
+        assert!(parse_reward("challenges 10").is_err());
+        assert!(parse_reward("<@&42> scenarios").is_err());
+    }
+}
@@ -1,13 +1,52 @@
 //! Collection of commands for Events with challeneges with group wide completion
 use crate::{
-    models::{challenge, event::Event},
-    utils::{self, PostgresPool},
+    embed::{self, Card},
+    models::{
+        challenge::{self, Challenge},
+        event::Event,
+        scenario::Scenario,
+    },
+    utils::PostgresPool,
 };
 use serenity::{
     framework::standard::{macros::command, CommandResult},
     model::channel::Message,
     prelude::Context,
 };
+use std::collections::BTreeMap;
+
+/// The challenges grouped under the scenario they're for. Unreachable until `cgroup` and
+/// `cgroupall` are registered in the Event group.
+#[allow(dead_code)]
+fn challenges_card(
+    title: &str,
+    event: &Event,
+    scenarios: &BTreeMap<Scenario, Vec<Challenge>>,
+) -> Card {
+    Card {
+        fields: scenarios
+            .iter()
+            .map(|(scenario, challenges)| {
+                (
+                    format!("{} (Code: {})", scenario.title, scenario.code),
+                    challenges
+                        .iter()
+                        .map(|challenge| {
+                            format!(
+                                "- (Code: **{}**) *{}* - {}",
+                                challenge.code,
+                                challenge.name,
+                                challenge.description.as_deref().unwrap_or("")
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                )
+            })
+            .collect(),
+        ..Card::for_event(title, event)
+    }
+}
 
 #[command]
 #[aliases("challenge", "challenges")]
@@ -42,10 +81,8 @@ pub async fn cgroup(ctx: &Context, msg: &Message) -> CommandResult {
     }
 
     let scenarios = challenge::group_by_scenario(challenges);
-
-    for content in utils::format_challenges_by_scenario(scenarios.iter()) {
-        msg.channel_id.say(&ctx.http, content).await?;
-    }
+    let card = challenges_card("Group Challenges Left", &event, &scenarios);
+    embed::send_card(ctx, pool, msg, &card).await?;
 
     Ok(())
 }
@@ -83,10 +120,8 @@ pub async fn cgroupall(ctx: &Context, msg: &Message) -> CommandResult {
     }
 
     let scenarios = challenge::group_by_scenario(challenges);
-
-    for content in utils::format_challenges_by_scenario(scenarios.iter()) {
-        msg.channel_id.say(&ctx.http, content).await?;
-    }
+    let card = challenges_card("All Group Challenges", &event, &scenarios);
+    embed::send_card(ctx, pool, msg, &card).await?;
 
     Ok(())
 }
@@ -113,20 +148,22 @@ pub async fn cgroupprogress(ctx: &Context, msg: &Message) -> CommandResult {
         }
     };
 
-    let all_challenges = event.find_all_challenges(&pool).await?;
-    let completed_challenges = event.find_completed_challenges(&pool).await?;
-
-    msg.channel_id
-        .say(
-            &ctx.http,
-            format!(
-                "We've completed {} of {} total challenges: {:.2}%",
-                completed_challenges.len(),
-                all_challenges.len(),
-                (completed_challenges.len() as f32 / all_challenges.len() as f32) * 100.0
-            ),
-        )
-        .await?;
+    let progress = event.find_group_challenge_progress(pool).await?;
+
+    let card = Card {
+        lines: match progress.percent() {
+            Some(perc) => vec![
+                embed::progress_bar(perc),
+                format!(
+                    "We've completed {} of {} total challenges: {:.2}%",
+                    progress.completed, progress.total, perc
+                ),
+            ],
+            None => vec!["No challenges are registered with the event yet.".to_string()],
+        },
+        ..Card::for_event(format!("{} Progress", event.name), &event)
+    };
+    embed::send_card(ctx, pool, msg, &card).await?;
 
     Ok(())
 }
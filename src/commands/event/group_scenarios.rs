@@ -1,71 +1,255 @@
 //! Collection of commands for Events with scenario based group wide completion
 
+use super::{milestones, rewards, status};
 use crate::{
-    commands::quest,
-    models::{event::Event, user::User},
+    commands::{
+        profile,
+        quest::{self, draw, vote},
+    },
+    embed::{self, Card},
+    models::{
+        achievement::{AchievementSource, Activity},
+        event::Event,
+        scenario::Scenario,
+        user::User,
+    },
     utils::{self, PostgresPool},
 };
 use serenity::{
     framework::standard::{macros::command, Args, CommandResult},
-    model::channel::Message,
+    model::{channel::Message, user::User as DiscordUser},
     prelude::Context,
 };
+use sqlx::postgres::PgPool;
+use std::time::Duration;
+
+/// Format the event's drawn quests with their codes, along with the seed that draws them again
+pub fn format_event_draw(scenarios: &[quest::Scenario], seed: &str) -> String {
+    format!(
+        "{}\nSeed: `{}`",
+        utils::format_collection(
+            &scenarios
+                .iter()
+                .map(|scenario| {
+                    format!(
+                        "{} from {} **with Code**: {}",
+                        scenario.title, scenario.set_name, scenario.code
+                    )
+                })
+                .collect(),
+        ),
+        seed
+    )
+}
+
+/// The event's drawn quests with their sets and codes, along with the seed that draws them again
+fn event_draw_card(event: &Event, scenarios: &[quest::Scenario], seed: &str) -> Card {
+    Card {
+        fields: scenarios
+            .iter()
+            .enumerate()
+            .map(|(i, scenario)| {
+                (
+                    format!("{}.) {}", i + 1, scenario.title),
+                    format!("{} **with Code**: {}", scenario.set_name, scenario.code),
+                )
+            })
+            .collect(),
+        footer: Some(format!("Seed: {}", seed)),
+        ..Card::for_event(format!("Quests for {}", event.name), event)
+    }
+}
+
+/// Announce the quest that won the vote, checking it out for whoever drew the quests
+pub async fn checkout_vote(
+    ctx: &Context,
+    pool: &PgPool,
+    event: &Event,
+    author: &DiscordUser,
+    winner: Option<&quest::Scenario>,
+) -> anyhow::Result<String> {
+    let scenario = match winner {
+        Some(scenario) => scenario,
+        None => return Ok("No votes were cast.".to_string()),
+    };
+    let user = User::find_or_create(pool, author.id.as_u64(), &author.name).await?;
+    let reserved = if event.checkout_scenario(pool, user.id, scenario.id).await? > 0 {
+        status::refresh(ctx, pool, event).await;
+        "Reserving it for 2 hours."
+    } else {
+        "It's already reserved."
+    };
+
+    Ok(format!(
+        "The vote is in: **{}** from {}. {}",
+        scenario.title, scenario.set_name, reserved
+    ))
+}
+
+/// The event's scenarios matching a code, alias or title, best matches first
+pub async fn match_scenarios(
+    pool: &PgPool,
+    event: &Event,
+    query: &str,
+) -> anyhow::Result<Vec<Scenario>> {
+    let alias = Scenario::find_by_alias(pool, query).await?;
+    let scenarios = event.find_scenarios(pool).await?;
+
+    Ok(utils::find_matches(
+        alias.as_ref().map_or(query, |scenario| &scenario.code),
+        &scenarios,
+        |scenario| &scenario.code,
+        |scenario| &scenario.title,
+    )
+    .into_iter()
+    .cloned()
+    .collect())
+}
+
+/// Mark the scenario as complete for the event, crediting the author. Returns the reply along with
+/// whether anything was recorded.
+pub async fn complete_scenario(
+    ctx: &Context,
+    pool: &PgPool,
+    event: &Event,
+    author: &DiscordUser,
+    scenario: &Scenario,
+) -> anyhow::Result<(String, bool)> {
+    let user = User::find_or_create(pool, author.id.as_u64(), &author.name).await?;
+
+    let completion = event.complete_scenario(pool, scenario.id, user.id).await?;
+    if !completion.found {
+        return Ok((
+            format!("Quest **{}** isn't part of the event.", scenario.title),
+            false,
+        ));
+    }
+    status::refresh(ctx, pool, event).await;
+
+    let mut lines = vec![format!("Completed Quest: {}", scenario.title)];
+    if completion.credited {
+        lines.extend(
+            profile::award(
+                pool,
+                &user,
+                &Activity {
+                    source: AchievementSource::Quest,
+                    scenario_id: Some(scenario.id),
+                    victory: Some(true),
+                    difficulty: None,
+                },
+            )
+            .await,
+        );
+    }
+
+    Ok((lines.join("\n"), true))
+}
+
+/// Reserve the scenario for 2 hours
+pub async fn checkout_scenario(
+    ctx: &Context,
+    pool: &PgPool,
+    event: &Event,
+    author: &DiscordUser,
+    scenario: &Scenario,
+) -> anyhow::Result<String> {
+    let user = User::find_or_create(pool, author.id.as_u64(), &author.name).await?;
+
+    Ok(
+        if event.checkout_scenario(pool, user.id, scenario.id).await? > 0 {
+            status::refresh(ctx, pool, event).await;
+            format!("Reserving Quest **{}**", scenario.title)
+        } else {
+            format!("Quest **{}** is already reserved.", scenario.title)
+        },
+    )
+}
+
+/// How much of the event the group has completed
+pub async fn progress_card(pool: &PgPool, event: &Event) -> anyhow::Result<Card> {
+    Ok(Card {
+        lines: match event.find_progress(pool).await? {
+            Some(perc) => vec![
+                embed::progress_bar(perc),
+                format!("The fellowship has completed {:.2}% of quests.", perc),
+            ],
+            None => vec!["Could not calculate progress.".to_string()],
+        },
+        ..Card::for_event(format!("{} Progress", event.name), event)
+    })
+}
 
 #[command]
-#[min_args(0)]
-#[max_args(1)]
 #[aliases(quest)]
-#[usage = "<quantity=default:3>"]
+#[usage = "<quantity=default:3> [filters]"]
 #[example = ""]
 #[example = "5"]
-/// Return a list of quests remaining for the event
+#[example = "3 cycle:dwarrowdelf exclude:saga difficulty:<=5 weighted"]
+#[example = "3 vote"]
+/// Return a list of quests remaining for the event. This takes the same filters and options as
+/// `quest`. If the event has a seed, it's used unless another one is given. When the `vote` is
+/// over, the winning quest is checked out.
 pub async fn equest(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let quantity = args.single::<i64>().unwrap_or(quest::DEFAULT_QUESTS_NUM);
+    let quantity = args
+        .single::<usize>()
+        .unwrap_or(quest::DEFAULT_QUESTS_NUM as usize);
+    let (filter, options) = match quest::parse_draw_args(args.rest(), quantity) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            msg.channel_id.say(&ctx.http, err.to_string()).await?;
+
+            return Ok(());
+        }
+    };
     let data = ctx.data.read().await;
     let pool = data
         .get::<PostgresPool>()
         .expect("Expected PostgresPool in TypeMap.");
 
+    if quest::is_unrated_difficulty(pool, &filter).await? {
+        msg.channel_id
+            .say(&ctx.http, quest::UNRATED_DIFFICULTY)
+            .await?;
+
+        return Ok(());
+    }
+
     if let Some(event) = Event::find_by_active(pool, true).await? {
-        let scenarios = sqlx::query!(
-            r#"
-SELECT scenarios.title, sets.name AS set_name, scenarios.code
-FROM scenarios, events_scenarios, sets
-WHERE scenarios.id = events_scenarios.scenario_id
-    AND events_scenarios.event_id = $1
-    AND scenarios.set_id = sets.id
-    AND events_scenarios.complete = false
-    AND (events_scenarios.checkout IS NULL OR events_scenarios.checkout < CURRENT_TIMESTAMP - INTERVAL '2 hours')
-ORDER BY RANDOM()
-LIMIT $2
-"#,
-            event.id,
-            quantity,
-        )
-        .fetch_all(pool)
-        .await?;
+        let candidates =
+            quest::find_event_candidates(pool, event.id, &filter, *msg.author.id.as_u64()).await?;
+        let seed = options.seed_or(event.seed.as_deref());
+        let scenarios = draw::draw(candidates, quantity, &options, &mut draw::seeded_rng(&seed));
 
         if scenarios.is_empty() {
             msg.channel_id
                 .say(&ctx.http, "No more quests registered with this event.")
                 .await?;
         } else {
-            msg.channel_id
-                .say(
-                    &ctx.http,
-                    utils::format_collection(
-                        &scenarios
-                            .iter()
-                            .map(|scenario| {
-                                format!(
-                                    "{} from {} **with Code**: {}",
-                                    scenario.title, scenario.set_name, scenario.code
-                                )
-                            })
-                            .collect(),
-                    ),
+            let message =
+                embed::send_card(ctx, pool, msg, &event_draw_card(&event, &scenarios, &seed))
+                    .await?;
+
+            if let (Some(message), Some(window)) =
+                (message, options.vote.filter(|_| scenarios.len() > 1))
+            {
+                let winner = vote::collect_votes(
+                    ctx,
+                    &message,
+                    scenarios.len(),
+                    Duration::from_secs(window),
                 )
                 .await?;
+                let message = checkout_vote(
+                    ctx,
+                    pool,
+                    &event,
+                    &msg.author,
+                    winner.and_then(|index| scenarios.get(index)),
+                )
+                .await?;
+                msg.channel_id.say(&ctx.http, message).await?;
+            }
         }
     } else {
         msg.channel_id
@@ -78,11 +262,14 @@ LIMIT $2
 
 #[command]
 #[min_args(1)]
-#[usage = "<scenario code>"]
+#[usage = "<scenario code|alias|title>"]
 #[example = "0101"]
-/// Mark a scenario as complete
-pub async fn complete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    if let Ok(code) = args.single::<String>() {
+#[example = "PtM"]
+#[example = "mirkwood"]
+/// Mark a scenario as complete. The scenario can be given by its code, alias or title.
+pub async fn complete(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest().trim();
+    if !query.is_empty() {
         let data = ctx.data.read().await;
         let pool = data
             .get::<PostgresPool>()
@@ -99,50 +286,41 @@ pub async fn complete(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
             }
         };
 
-        let scenario;
-        if let Ok(s) = sqlx::query!(
-            r#"
-SELECT id, title
-FROM scenarios
-WHERE code = $1
-"#,
-            code
+        let scenarios = match_scenarios(pool, &event, query).await?;
+        let scenario = match utils::pick_match(
+            ctx,
+            msg,
+            query,
+            scenarios.iter().collect(),
+            |scenario| &scenario.code,
+            |scenario| &scenario.title,
         )
-        .fetch_one(pool)
-        .await
+        .await?
         {
-            scenario = s;
-        } else {
-            msg.channel_id
-                .say(
-                    &ctx.http,
-                    format!("No scenario found by that code: {}", code),
-                )
-                .await?;
-
-            return Ok(());
-        }
+            Some(scenario) => scenario,
+            None => {
+                msg.channel_id
+                    .say(&ctx.http, format!("No scenario found by: {}", query))
+                    .await?;
 
-        let row_count = sqlx::query!(
-            r#"
-UPDATE events_scenarios
-SET complete = true,
-    updated_at = CURRENT_TIMESTAMP
-WHERE event_id = $1
-    AND scenario_id = $2
-"#,
-            event.id,
-            scenario.id,
-        )
-        .execute(pool)
-        .await?
-        .rows_affected();
+                return Ok(());
+            }
+        };
 
-        // Not sure this else can be triggered
-        if row_count > 0 {
-            msg.channel_id
-                .say(&ctx.http, format!("Completed Quest: {}", scenario.title))
-                .await?;
+        let (message, recorded) =
+            complete_scenario(ctx, pool, &event, &msg.author, scenario).await?;
+        msg.channel_id.say(&ctx.http, message).await?;
+        if recorded {
+            milestones::announce(ctx, pool, &event, msg.channel_id).await;
+            rewards::grant(
+                ctx,
+                pool,
+                &event,
+                msg.guild_id,
+                msg.channel_id,
+                &[&msg.author],
+            )
+            .await;
         }
     }
 
@@ -150,12 +328,16 @@ WHERE event_id = $1
 }
 
 #[command]
-#[num_args(1)]
-#[usage = "<scenario code>"]
+#[min_args(1)]
+#[usage = "<scenario code|alias|title>"]
 #[example = "0101"]
-/// Claim a Quest for 2 hours. This will hide it from the equest command.
-pub async fn checkout(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    if let Ok(code) = args.single::<String>() {
+#[example = "FotS"]
+#[example = "khazad dum"]
+/// Claim a Quest for 2 hours. This will hide it from the equest command. The quest can be given by
+/// its code, alias or title.
+pub async fn checkout(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest().trim();
+    if !query.is_empty() {
         let data = ctx.data.read().await;
         let pool = data
             .get::<PostgresPool>()
@@ -172,61 +354,29 @@ pub async fn checkout(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
             return Ok(());
         }
 
-        let scenario;
-        if let Ok(s) = sqlx::query!(
-            r#"
-SELECT id, title
-FROM scenarios
-WHERE code = $1
-"#,
-            code
+        let scenarios = match_scenarios(pool, &event, query).await?;
+        let scenario = match utils::pick_match(
+            ctx,
+            msg,
+            query,
+            scenarios.iter().collect(),
+            |scenario| &scenario.code,
+            |scenario| &scenario.title,
         )
-        .fetch_one(pool)
-        .await
-        {
-            scenario = s;
-        } else {
-            msg.channel_id
-                .say(
-                    &ctx.http,
-                    format!("No scenario found by that code: {}", code),
-                )
-                .await?;
-
-            return Ok(());
-        }
-
-        let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
-        if let Ok(result) = sqlx::query!(
-            r#"
-UPDATE events_scenarios
-SET checkout = CURRENT_TIMESTAMP,
-    checkout_user_id = $1,
-    updated_at = CURRENT_TIMESTAMP
-WHERE event_id = $2
-    AND scenario_id = $3
-    AND (checkout IS NULL OR checkout < CURRENT_TIMESTAMP - INTERVAL '2 hours')
-"#,
-            user.id,
-            event.id,
-            scenario.id,
-        )
-        .execute(pool)
-        .await
+        .await?
         {
-            if result.rows_affected() > 0 {
-                msg.channel_id
-                    .say(&ctx.http, format!("Reserving Quest **{}**", scenario.title))
-                    .await?;
-            } else {
+            Some(scenario) => scenario,
+            None => {
                 msg.channel_id
-                    .say(
-                        &ctx.http,
-                        format!("Quest **{}** is already reserved.", scenario.title),
-                    )
+                    .say(&ctx.http, format!("No scenario found by: {}", query))
                     .await?;
+
+                return Ok(());
             }
-        }
+        };
+
+        let message = checkout_scenario(ctx, pool, &event, &msg.author, scenario).await?;
+        msg.channel_id.say(&ctx.http, message).await?;
     }
 
     Ok(())
@@ -243,33 +393,7 @@ pub async fn progress(ctx: &Context, msg: &Message) -> CommandResult {
         .expect("Expected PostgresPool in TypeMap.");
 
     if let Some(event) = Event::find_by_active(pool, true).await? {
-        let calc = sqlx::query!(
-            r#"
-SELECT (cnt/total::float)*100 AS perc
-FROM (
-    SELECT COUNT(*) AS total,
-        SUM(CASE WHEN complete = true THEN 1 ELSE 0 END) AS cnt
-    FROM events_scenarios
-    WHERE event_id = $1
-) x
-"#,
-            event.id
-        )
-        .fetch_one(pool)
-        .await?;
-
-        if let Some(perc) = calc.perc {
-            msg.channel_id
-                .say(
-                    &ctx.http,
-                    format!("The fellowship has completed {:.2}% of quests.", perc),
-                )
-                .await?;
-        } else {
-            msg.channel_id
-                .say(&ctx.http, "Could not calculate progress.")
-                .await?;
-        }
+        embed::send_card(ctx, pool, msg, &progress_card(pool, &event).await?).await?;
     }
 
     Ok(())
@@ -1,17 +1,22 @@
 use crate::utils::PostgresPool;
 use crate::{
-    commands::quest,
-    models::{event::Event, user::User},
+    commands::{
+        event::{complete_challenge, milestones, rewards},
+        quest::{self, filter::ScenarioFilter},
+    },
+    embed::{self, Card},
+    models::event::Event,
     utils,
 };
 use serenity::{
     framework::standard::{macros::command, Args, CommandResult},
-    model::channel::Message,
+    model::{channel::Message, user::User as DiscordUser},
     prelude::Context,
-    utils::MessageBuilder,
 };
 
 const KANG_DEFAULT_MODE: &str = "all";
+/// Attribute marking the challenges that are part of the Council of 100 Kangs
+pub const KANG_ATTRIBUTE: &str = "Council of 100 Kangs";
 
 fn valid_mode(mode: impl AsRef<str>) -> bool {
     if !vec!["standard", "expert", "all"].contains(&mode.as_ref().to_lowercase().as_str()) {
@@ -93,13 +98,24 @@ WHERE 'LeacgueCG Con 2021: The Return of the Kang'=ANY(attributes)
             .say(&ctx.http, "No Kangs could be found for that query.")
             .await?;
     } else {
-        for message in utils::format_large_collection(
+        let lines = utils::number_collection(
             &challenges
                 .iter()
                 .map(|challenge| format!("**{}** with Code: *{}*", challenge.name, challenge.code))
-                .collect(),
-        ) {
-            msg.channel_id.say(&ctx.http, message).await?;
+                .collect::<Vec<String>>(),
+        );
+        if embed::plain_text(pool, msg).await? {
+            utils::browse_pages(ctx, msg, &lines).await?;
+        } else {
+            let title = format!("{} ({})", KANG_ATTRIBUTE, mode.to_lowercase());
+            let card = Card {
+                lines,
+                ..match Event::find_by_active(pool, true).await? {
+                    Some(event) => Card::for_event(title, &event),
+                    None => Card::new(title),
+                }
+            };
+            embed::send_card(ctx, pool, msg, &card).await?;
         }
     }
 
@@ -107,25 +123,30 @@ WHERE 'LeacgueCG Con 2021: The Return of the Kang'=ANY(attributes)
 }
 
 #[command]
-#[min_args(0)]
-#[max_args(2)]
-#[usage = "<mode=default:all> <quantity=default:3>"]
+#[usage = "<mode=default:all> <quantity=default:3> [owned|owned:@player @player]"]
+#[example = "expert 3 owned"]
 /// Get some Kangs to fight!
 /// supported modes: all, standard, expert
+/// `owned` leaves out Kangs tied to scenarios from sets you, or everyone mentioned, don't own.
 pub async fn mission(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let mode = args
-        .single::<String>()
-        .unwrap_or(String::from(KANG_DEFAULT_MODE));
+    let mode = match args.current() {
+        Some(mode) if valid_mode(mode) => args.single::<String>()?,
+        _ => String::from(KANG_DEFAULT_MODE),
+    };
     let quantity = args.single::<i64>().unwrap_or(quest::DEFAULT_QUESTS_NUM);
-    if !valid_mode(&mode) {
-        msg.channel_id
-            .say(
-                &ctx.http,
-                "The only valid modes are 'standard', 'expert', 'all'.",
-            )
-            .await?;
-        return Ok(());
-    }
+    let filter = match ScenarioFilter::parse(args.rest()) {
+        Ok(filter) if filter.is_empty() || filter.owned => filter,
+        Ok(_) => {
+            msg.channel_id
+                .say(&ctx.http, "Missions can only be filtered with 'owned'.")
+                .await?;
+            return Ok(());
+        }
+        Err(err) => {
+            msg.channel_id.say(&ctx.http, err.to_string()).await?;
+            return Ok(());
+        }
+    };
     let data = ctx.data.read().await;
     let pool = data
         .get::<PostgresPool>()
@@ -153,11 +174,18 @@ WHERE challenges_events.event_id = $1
         SELECT challenges_events_id
         FROM challenges_events_users
     )
+    AND (NOT $3::BOOLEAN OR challenges.scenario_id IS NULL OR challenges.scenario_id IN (
+        SELECT scenarios.id
+        FROM scenarios, sets_owned_by_all($4) AS owned
+        WHERE scenarios.set_id = owned.set_id
+    ))
 ORDER BY RANDOM()
 LIMIT $2
 "#,
             event.id,
             quantity,
+            filter.owned,
+            &filter.owner_ids(*msg.author.id.as_u64()),
         )
         .fetch_all(pool)
         .await?
@@ -180,12 +208,19 @@ WHERE challenges_events.event_id = $1
         SELECT challenges_events_id
         FROM challenges_events_users
     )
+    AND (NOT $4::BOOLEAN OR challenges.scenario_id IS NULL OR challenges.scenario_id IN (
+        SELECT scenarios.id
+        FROM scenarios, sets_owned_by_all($5) AS owned
+        WHERE scenarios.set_id = owned.set_id
+    ))
 ORDER BY RANDOM()
 LIMIT $3
 "#,
             event.id,
             mode,
             quantity,
+            filter.owned,
+            &filter.owner_ids(*msg.author.id.as_u64()),
         )
         .fetch_all(pool)
         .await?
@@ -202,19 +237,19 @@ LIMIT $3
             .say(&ctx.http, "Couldn't find any Kangs.")
             .await?;
     } else {
-        msg.channel_id
-            .say(
-                &ctx.http,
-                utils::format_collection(
-                    &challenges
-                        .iter()
-                        .map(|challenge| {
-                            format!("**{}** with Code: *{}*", challenge.name, challenge.code)
-                        })
-                        .collect(),
-                ),
-            )
-            .await?;
+        utils::say_paginated(
+            ctx,
+            msg.channel_id,
+            &utils::number_collection(
+                &challenges
+                    .iter()
+                    .map(|challenge| {
+                        format!("**{}** with Code: *{}*", challenge.name, challenge.code)
+                    })
+                    .collect::<Vec<String>>(),
+            ),
+        )
+        .await?;
     }
 
     Ok(())
@@ -241,42 +276,19 @@ pub async fn kprogress(ctx: &Context, msg: &Message) -> CommandResult {
         }
     };
 
-    let calc = sqlx::query!(
-        r#"
-WITH completed AS (
-    SELECT COUNT(DISTINCT(challenges_events_users.challenges_events_id)) as cnt
-    FROM challenges_events_users, challenges_events, challenges
-    WHERE challenges_events.event_id = $1
-        AND challenges_events.id = challenges_events_users.challenges_events_id
-        AND challenges.id = challenges_events.challenge_id
-        AND 'Council of 100 Kangs'=ANY(challenges.attributes)
-    ), total AS (
-    SELECT COUNT(challenges_events.id) AS cnt
-    FROM challenges_events, challenges
-    WHERE challenges_events.event_id = $1
-        AND challenges.id = challenges_events.challenge_id
-        AND 'Council of 100 Kangs'=ANY(challenges.attributes)
-    )
-SELECT (completed.cnt/total.cnt::float)*100 AS perc
-FROM completed, total
-"#,
-        event.id
-    )
-    .fetch_one(pool)
-    .await?;
+    let perc = event.find_challenge_progress(pool, KANG_ATTRIBUTE).await?;
 
-    if let Some(perc) = calc.perc {
-        msg.channel_id
-            .say(
-                &ctx.http,
+    let card = Card {
+        lines: match perc {
+            Some(perc) => vec![
+                embed::progress_bar(perc),
                 format!("The heroes have completed {:.2}% of Kangs.", perc),
-            )
-            .await?;
-    } else {
-        msg.channel_id
-            .say(&ctx.http, "Could not calculate progress.")
-            .await?;
-    }
+            ],
+            None => vec!["Could not calculate progress.".to_string()],
+        },
+        ..Card::for_event(format!("{} Progress", KANG_ATTRIBUTE), &event)
+    };
+    embed::send_card(ctx, pool, msg, &card).await?;
 
     Ok(())
 }
@@ -284,23 +296,21 @@ FROM completed, total
 #[command]
 #[min_args(1)]
 #[aliases(complete)]
-#[usage = "<code>"]
+#[usage = "<code|name>"]
 #[example = "ROTK2021-MCS36"]
-/// Conquer Kang!
-pub async fn conquer(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let code = match args.single::<String>() {
-        Ok(code) => code,
-        Err(_) => {
-            msg.channel_id
-                .say(
-                    &ctx.http,
-                    format!("This command taske a code as an argument."),
-                )
-                .await?;
+/// Conquer Kang! The challenge can be given by its code or name.
+pub async fn conquer(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = utils::strip_mentions(args.rest());
+    if query.is_empty() {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                "This command takes a code or name as an argument.",
+            )
+            .await?;
 
-            return Ok(());
-        }
-    };
+        return Ok(());
+    }
     let data = ctx.data.read().await;
     let pool = data
         .get::<PostgresPool>()
@@ -316,87 +326,34 @@ pub async fn conquer(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
             return Ok(());
         }
     };
-    let challenge_event = match sqlx::query!(
-        r#"
-SELECT challenges.name, challenges_events.id
-FROM challenges, challenges_events
-WHERE challenges_events.event_id = $1
-    AND challenges.code = $2
-    AND challenges_events.challenge_id = challenges.id
-    AND 'Council of 100 Kangs'=ANY(challenges.attributes)
-"#,
-        event.id,
-        code
+    let challenges = event.find_challenges(pool, Some(KANG_ATTRIBUTE)).await?;
+    let challenge_event = match utils::resolve(
+        ctx,
+        msg,
+        &query,
+        &challenges,
+        |challenge| &challenge.code,
+        |challenge| &challenge.name,
     )
-    .fetch_one(pool)
-    .await
+    .await?
     {
-        Ok(challenge) => challenge,
-        Err(_) => {
+        Some(challenge) => challenge,
+        None => {
             msg.channel_id
-                .say(
-                    &ctx.http,
-                    format!("No scenario found by that code: {}", code),
-                )
+                .say(&ctx.http, format!("No challenge found by: {}", query))
                 .await?;
 
             return Ok(());
         }
     };
 
-    let mut reply = MessageBuilder::new();
-    let mut mentioned_users: Vec<&serenity::model::user::User> =
-        msg.mentions.iter().map(|u| u).collect();
-    mentioned_users.insert(0, &msg.author);
-    let mut completed_users: Vec<Option<&serenity::model::user::User>> = Vec::new();
-
-    for discord_user in mentioned_users.iter() {
-        let user = User::find_or_create(pool, discord_user.id.as_u64(), &discord_user.name).await?;
-        let row_count = sqlx::query!(
-            r#"
-INSERT INTO challenges_events_users ( challenges_events_id, user_id )
-VALUES ( $1, $2 )
-ON CONFLICT DO NOTHING
-"#,
-            challenge_event.id,
-            user.id
-        )
-        .execute(pool)
-        .await?
-        .rows_affected();
-
-        completed_users.push(if row_count > 0 {
-            Some(discord_user)
-        } else {
-            None
-        });
-    }
-
-    let completed_users: Vec<_> = completed_users
-        .into_iter()
-        .filter_map(|user| user)
-        .collect();
-    if completed_users.is_empty() {
-        for discord_user in mentioned_users.iter() {
-            reply.mention(*discord_user);
-            reply.push(" ");
-        }
-        reply.push(format!(
-            "All users have already completed challenge '{}'",
-            challenge_event.name
-        ));
-    } else {
-        for discord_user in completed_users.iter() {
-            reply.mention(*discord_user);
-            reply.push(" ");
-        }
-        reply.push(format!(
-            "You completed challenge '{}'",
-            challenge_event.name
-        ));
-    }
+    let mut players: Vec<&DiscordUser> = vec![&msg.author];
+    players.extend(msg.mentions.iter());
+    let reply = complete_challenge(pool, challenge_event, &players).await?;
 
-    utils::check_msg(msg.channel_id.say(&ctx.http, &reply.build()).await);
+    utils::check_msg(msg.channel_id.say(&ctx.http, reply).await);
+    milestones::announce(ctx, pool, &event, msg.channel_id).await;
+    rewards::grant(ctx, pool, &event, msg.guild_id, msg.channel_id, &players).await;
 
     Ok(())
 }
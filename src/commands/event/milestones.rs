@@ -0,0 +1,101 @@
+//! Announcements for when the group reaches its milestones
+use super::kang::KANG_ATTRIBUTE;
+use crate::{
+    models::{
+        event::Event,
+        milestone::{Milestone, MilestoneKind},
+    },
+    utils,
+};
+use serenity::{model::id::ChannelId, prelude::Context};
+use sqlx::postgres::PgPool;
+
+/// How much of the event's progress of the kind is complete, as a percentage
+async fn find_progress(
+    pool: &PgPool,
+    event: &Event,
+    kind: MilestoneKind,
+) -> anyhow::Result<Option<f64>> {
+    match kind {
+        MilestoneKind::Quests => event.find_progress(pool).await,
+        MilestoneKind::Challenges => Ok(event.find_group_challenge_progress(pool).await?.percent()),
+        MilestoneKind::Kangs => event.find_challenge_progress(pool, KANG_ATTRIBUTE).await,
+    }
+}
+
+pub fn format_announcement(event: &Event, kind: MilestoneKind, milestone: &Milestone) -> String {
+    format!(
+        "{}The fellowship has completed {}% of the {} in {}!",
+        milestone
+            .role_id
+            .map(|role_id| format!("<@&{}> ", role_id))
+            .unwrap_or_default(),
+        milestone.percent,
+        match kind {
+            MilestoneKind::Kangs => "Kangs",
+            _ => kind.as_str(),
+        },
+        event.name
+    )
+}
+
+async fn try_announce(
+    ctx: &Context,
+    pool: &PgPool,
+    event: &Event,
+    channel_id: ChannelId,
+) -> anyhow::Result<()> {
+    for kind in MilestoneKind::ALL {
+        let progress = match find_progress(pool, event, kind).await? {
+            Some(progress) => progress,
+            None => continue,
+        };
+
+        for milestone in Milestone::claim_reached(pool, event.id, kind, progress).await? {
+            channel_id
+                .say(&ctx.http, format_announcement(event, kind, &milestone))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Announce in the channel any milestones the group has just reached
+pub async fn announce(ctx: &Context, pool: &PgPool, event: &Event, channel_id: ChannelId) {
+    utils::log_failure(
+        try_announce(ctx, pool, event, channel_id).await,
+        &format!("announce the milestones of '{}'", event.name),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_formats_announcements() {
+        let event = Event {
+            id: 1,
+            name: "Con of the Rings".to_string(),
+            seed: None,
+            color: None,
+            thumbnail_url: None,
+        };
+        let milestone = |role_id| Milestone {
+            kind: "kangs".to_string(),
+            percent: 50,
+            role_id,
+            announced: true,
+        };
+
+        assert_eq!(
+            format_announcement(&event, MilestoneKind::Kangs, &milestone(None)),
+            "The fellowship has completed 50% of the Kangs in Con of the Rings!"
+        );
+        assert_eq!(
+            format_announcement(&event, MilestoneKind::Quests, &milestone(Some(42))),
+            "<@&42> The fellowship has completed 50% of the quests in Con of the Rings!"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Roles handed out to players for what they complete during an event
+use crate::{
+    models::{
+        event::Event,
+        reward::{EarnedReward, Reward},
+    },
+    utils,
+};
+use serenity::{
+    builder::ParseValue,
+    model::{
+        id::{ChannelId, GuildId},
+        user::User as DiscordUser,
+    },
+    prelude::Context,
+};
+use sqlx::postgres::PgPool;
+use tracing::{error, info};
+
+pub fn format_grant(earned: &EarnedReward) -> String {
+    format!(
+        "<@{}> earned the <@&{}> role!",
+        earned.discord_id, earned.role_id
+    )
+}
+
+async fn try_grant(
+    ctx: &Context,
+    pool: &PgPool,
+    event: &Event,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    players: &[&DiscordUser],
+) -> anyhow::Result<()> {
+    let discord_ids: Vec<i64> = players.iter().map(|player| player.id.0 as i64).collect();
+
+    for earned in Reward::find_earned(pool, event.id, &discord_ids).await? {
+        if !Reward::claim(pool, earned.reward_id, earned.user_id).await? {
+            continue;
+        }
+
+        if let Err(why) = ctx
+            .http
+            .add_member_role(guild_id.0, earned.discord_id as u64, earned.role_id as u64)
+            .await
+        {
+            Reward::unclaim(pool, earned.reward_id, earned.user_id).await?;
+            error!(
+                "Unable to give role {} to user {} for '{}': {:?}",
+                earned.role_id, earned.discord_id, event.name, why
+            );
+
+            continue;
+        }
+        info!(
+            "Gave role {} to user {} for '{}'",
+            earned.role_id, earned.discord_id, event.name
+        );
+
+        // only the player is pinged, not everyone with the role
+        channel_id
+            .send_message(&ctx.http, |m| {
+                m.content(format_grant(&earned))
+                    .allowed_mentions(|am| am.parse(ParseValue::Users))
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Give the players any roles they've earned in the event, waiting for a completion in a server
+pub async fn grant(
+    ctx: &Context,
+    pool: &PgPool,
+    event: &Event,
+    guild_id: Option<GuildId>,
+    channel_id: ChannelId,
+    players: &[&DiscordUser],
+) {
+    let guild_id = match guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+
+    utils::log_failure(
+        try_grant(ctx, pool, event, guild_id, channel_id, players).await,
+        &format!("give the rewards of '{}'", event.name),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_formats_grants() {
+        let earned = EarnedReward {
+            reward_id: 1,
+            role_id: 42,
+            user_id: 2,
+            discord_id: 7,
+        };
+
+        assert_eq!(format_grant(&earned), "<@7> earned the <@&42> role!");
+    }
+}
@@ -0,0 +1,254 @@
+//! The pinned message showing how an event is going, kept up to date as quests are completed and
+//! checked out
+use crate::{
+    embed::{self, Card},
+    models::event::{Checkout, Event, SetProgress},
+    utils,
+};
+use serenity::{
+    builder::CreateEmbed,
+    http::error::Error as HttpError,
+    model::id::{ChannelId, MessageId},
+    prelude::Context,
+};
+use sqlx::postgres::PgPool;
+
+/// Number of recently completed quests shown
+const RECENT_COMPLETIONS: i64 = 5;
+/// Number of sets shown before the rest are summed up, so the message stays within Discord's
+/// limits
+const MAX_SETS: usize = 30;
+
+fn format_set_progress(sets: &[SetProgress]) -> Vec<String> {
+    // sets still in progress are the interesting ones, so they go first
+    let mut sets: Vec<&SetProgress> = sets.iter().collect();
+    sets.sort_by_key(|set| set.completed == set.total);
+
+    let mut lines: Vec<String> = sets
+        .iter()
+        .take(MAX_SETS)
+        .map(|set| {
+            format!(
+                "{}{}: {} of {}",
+                if set.completed == set.total {
+                    "✓ "
+                } else {
+                    ""
+                },
+                set.name,
+                set.completed,
+                set.total
+            )
+        })
+        .collect();
+    if sets.len() > MAX_SETS {
+        lines.push(format!("…and {} more sets", sets.len() - MAX_SETS));
+    }
+
+    lines
+}
+
+fn format_checkouts(checkouts: &[Checkout]) -> String {
+    if checkouts.is_empty() {
+        return "Nothing is checked out.".to_string();
+    }
+
+    checkouts
+        .iter()
+        .map(|checkout| {
+            format!(
+                "{} (Code: {}) by {}, {} minutes left",
+                checkout.title,
+                checkout.code,
+                checkout.user_name.as_deref().unwrap_or("Unknown"),
+                checkout.minutes_left
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// The event's overall and per-set progress, checkouts and latest completions
+async fn status_card(pool: &PgPool, event: &Event) -> anyhow::Result<Card> {
+    let sets = event.find_set_progress(pool).await?;
+    let checkouts = event.find_checkouts(pool).await?;
+    let recent = event
+        .find_recent_completions(pool, RECENT_COMPLETIONS)
+        .await?;
+    let completed: i64 = sets.iter().map(|set| set.completed).sum();
+    let total: i64 = sets.iter().map(|set| set.total).sum();
+
+    let mut lines = match event.find_progress(pool).await? {
+        Some(perc) => vec![
+            embed::progress_bar(perc),
+            format!(
+                "The fellowship has completed {} of {} quests.",
+                completed, total
+            ),
+        ],
+        None => vec!["No quests are registered with the event yet.".to_string()],
+    };
+    if !sets.is_empty() {
+        lines.push(String::new());
+        lines.push("**Sets**".to_string());
+        lines.extend(format_set_progress(&sets));
+    }
+
+    Ok(Card {
+        lines,
+        fields: vec![
+            ("Checked Out".to_string(), format_checkouts(&checkouts)),
+            (
+                "Recently Completed".to_string(),
+                if recent.is_empty() {
+                    "Nothing yet.".to_string()
+                } else {
+                    recent
+                        .iter()
+                        .map(|scenario| format!("{} (Code: {})", scenario.title, scenario.code))
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                },
+            ),
+        ],
+        footer: Some("This message updates itself.".to_string()),
+        ..Card::for_event(format!("{} Status", event.name), event)
+    })
+}
+
+/// The status as it fits in one message
+enum Status {
+    Text(String),
+    Embed(CreateEmbed),
+}
+
+/// Render the status for the channel: its first embed, or its first page of plain text where
+/// embeds are turned off
+async fn render(
+    ctx: &Context,
+    pool: &PgPool,
+    event: &Event,
+    channel_id: ChannelId,
+) -> anyhow::Result<Status> {
+    let card = status_card(pool, event).await?;
+    let guild_id = channel_id
+        .to_channel(&ctx)
+        .await?
+        .guild()
+        .map(|channel| channel.guild_id);
+
+    Ok(if embed::guild_plain_text(pool, guild_id).await? {
+        Status::Text(card.to_plain_text().remove(0))
+    } else {
+        Status::Embed(card.to_embeds().remove(0))
+    })
+}
+
+/// Post the event's status message in the channel and pin it, replacing the one it had
+pub async fn post(
+    ctx: &Context,
+    pool: &PgPool,
+    event: &Event,
+    channel_id: ChannelId,
+) -> anyhow::Result<()> {
+    let old_status = event.find_status_message(pool).await?;
+
+    let status = render(ctx, pool, event, channel_id).await?;
+    let message = channel_id
+        .send_message(&ctx.http, |m| match status {
+            Status::Text(text) => m.content(text),
+            Status::Embed(embed) => m.set_embed(embed),
+        })
+        .await?;
+    // the new message is saved before the old one is deleted, otherwise deleting it would post the
+    // status all over again
+    event
+        .update_status_message(pool, channel_id.0, message.id.0)
+        .await?;
+    message.pin(&ctx).await?;
+
+    if let Some(old_status) = old_status {
+        // the old message may well be gone already
+        let _ = ChannelId(old_status.channel_id as u64)
+            .delete_message(&ctx.http, old_status.message_id as u64)
+            .await;
+    }
+
+    Ok(())
+}
+
+fn is_not_found(err: &HttpError) -> bool {
+    matches!(err, HttpError::UnsuccessfulRequest(response) if response.status_code.as_u16() == 404)
+}
+
+async fn try_refresh(ctx: &Context, pool: &PgPool, event: &Event) -> anyhow::Result<()> {
+    let status = match event.find_status_message(pool).await? {
+        Some(status) => status,
+        None => return Ok(()),
+    };
+    let channel_id = ChannelId(status.channel_id as u64);
+    let rendered = render(ctx, pool, event, channel_id).await?;
+
+    let edited = channel_id
+        .edit_message(&ctx.http, status.message_id as u64, |m| match rendered {
+            Status::Text(text) => m.content(text).set_embeds(Vec::new()),
+            Status::Embed(embed) => m.content("").set_embed(embed),
+        })
+        .await;
+    match edited {
+        // the message was deleted
+        Err(serenity::Error::Http(err)) if is_not_found(&err) => {
+            post(ctx, pool, event, channel_id).await
+        }
+        Err(err) => Err(err.into()),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Bring the event's status message up to date, posting it again if it was deleted
+pub async fn refresh(ctx: &Context, pool: &PgPool, event: &Event) {
+    utils::log_failure(
+        try_refresh(ctx, pool, event).await,
+        &format!("update the status of '{}'", event.name),
+    );
+}
+
+/// Post a status message again when it's deleted
+pub async fn restore(
+    ctx: &Context,
+    pool: &PgPool,
+    channel_id: ChannelId,
+    message_id: MessageId,
+) -> anyhow::Result<()> {
+    if let Some(event) = Event::find_by_status_message(pool, message_id.0).await? {
+        post(ctx, pool, &event, channel_id).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(name: &str, completed: i64, total: i64) -> SetProgress {
+        SetProgress {
+            name: name.to_string(),
+            completed,
+            total,
+        }
+    }
+
+    #[test]
+    fn it_lists_unfinished_sets_first() {
+        assert_eq!(
+            format_set_progress(&[set("Core Set", 3, 3), set("Khazad-dûm", 1, 3)]),
+            vec!["Khazad-dûm: 1 of 3", "✓ Core Set: 3 of 3"]
+        );
+
+        let sets: Vec<SetProgress> = (0..35).map(|i| set(&i.to_string(), 0, 1)).collect();
+        let lines = format_set_progress(&sets);
+        assert_eq!(lines.len(), MAX_SETS + 1);
+        assert_eq!(lines[MAX_SETS], "…and 5 more sets");
+    }
+}
@@ -0,0 +1,169 @@
+//! Collection of commands for logging games both inside and outside of events
+use crate::{
+    commands::{completionist::parse_result, profile},
+    models::{
+        achievement::{AchievementSource, Activity},
+        event::Event,
+        play::{Difficulty, Play},
+        scenario::Scenario,
+        user::User,
+    },
+    utils::PostgresPool,
+};
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::channel::Message,
+    prelude::Context,
+};
+
+/// Optional details for a logged play
+#[derive(Debug, Default, PartialEq)]
+pub struct PlayOptions {
+    pub players: Option<i16>,
+    pub difficulty: Difficulty,
+    pub notes: Option<String>,
+}
+
+/// Parse `players=N` and `mode=<difficulty>` out of the arguments. Anything else is kept as notes.
+pub fn parse_play_options<'a>(
+    tokens: impl Iterator<Item = &'a str>,
+) -> anyhow::Result<PlayOptions> {
+    let mut options = PlayOptions::default();
+    let mut notes = Vec::new();
+
+    for token in tokens {
+        if let Some(players) = token.strip_prefix("players=") {
+            let players = players
+                .parse::<i16>()
+                .ok()
+                .filter(|players| (1..=4).contains(players))
+                .ok_or_else(|| anyhow::anyhow!("Players must be a number from 1 to 4."))?;
+            options.players = Some(players);
+        } else if let Some(mode) = token.strip_prefix("mode=") {
+            options.difficulty = mode.parse()?;
+        } else {
+            notes.push(token);
+        }
+    }
+
+    if !notes.is_empty() {
+        options.notes = Some(notes.join(" "));
+    }
+
+    Ok(options)
+}
+
+#[command]
+#[min_args(2)]
+#[usage = "<scenario code|alias> <win|loss> [players=N] [mode=easy|normal|nightmare] [notes]"]
+#[example = "0101 win"]
+#[example = "0101 loss players=2 mode=nightmare Ungoliant's Spawn ate us"]
+/// Log a game. If there's an active event, the game is recorded against it.
+pub async fn log(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let code = args.single_quoted::<String>()?;
+    let victory = match args.single::<String>().ok().and_then(parse_result) {
+        Some(victory) => victory,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "The result must be either 'win' or 'loss'.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+    let options = match parse_play_options(args.rest().split_whitespace()) {
+        Ok(options) => options,
+        Err(err) => {
+            msg.channel_id.say(&ctx.http, err.to_string()).await?;
+
+            return Ok(());
+        }
+    };
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let scenario = match Scenario::find_by_alias_or_code(pool, &code).await? {
+        Some(scenario) => scenario,
+        None => {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!("No scenario found by that code or alias: {}", code),
+                )
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let event = Event::find_by_active(pool, true).await?;
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    Play {
+        user_id: user.id,
+        scenario_id: scenario.id,
+        event_id: event.as_ref().map(|event| event.id),
+        victory,
+        players: options.players,
+        difficulty: options.difficulty,
+        notes: options.notes,
+    }
+    .create(pool)
+    .await?;
+
+    let user_stats = Play::stats_by_user_and_scenario(pool, user.id, scenario.id).await?;
+    let stats = Play::stats_by_scenario(pool, scenario.id).await?;
+    let mut lines = vec![format!(
+        "Logged a {} against **{}** ({}). That was attempt #{} for you. Everyone has won {:.2}% of {} attempts.",
+        if victory { "win" } else { "loss" },
+        scenario.title,
+        options.difficulty,
+        user_stats.attempts,
+        stats.win_rate(),
+        stats.attempts
+    )];
+    lines.extend(
+        profile::award(
+            pool,
+            &user,
+            &Activity {
+                source: AchievementSource::Play,
+                scenario_id: Some(scenario.id),
+                victory: Some(victory),
+                difficulty: Some(options.difficulty),
+            },
+        )
+        .await,
+    );
+    msg.reply(&ctx.http, lines.join("\n")).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_play_options() {
+        let options =
+            parse_play_options("players=2 mode=nightmare Ungoliant's Spawn".split_whitespace())
+                .unwrap();
+
+        assert_eq!(
+            options,
+            PlayOptions {
+                players: Some(2),
+                difficulty: Difficulty::Nightmare,
+                notes: Some("Ungoliant's Spawn".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_play_options("".split_whitespace()).unwrap(),
+            PlayOptions::default()
+        );
+        assert!(parse_play_options("players=5".split_whitespace()).is_err());
+        assert!(parse_play_options("mode=hard".split_whitespace()).is_err());
+    }
+}
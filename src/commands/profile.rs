@@ -0,0 +1,122 @@
+//! Achievements players earn for what they play and complete, and the profile showing them
+use crate::{
+    embed::{self, Card},
+    models::{
+        achievement::{Achievement, AchievementProgress, Activity},
+        user::User,
+    },
+    utils::{self, PostgresPool},
+};
+use serenity::{
+    framework::standard::{macros::command, CommandResult},
+    model::channel::Message,
+    prelude::Context,
+};
+use sqlx::postgres::PgPool;
+
+pub fn format_earned(discord_id: i64, achievement: &Achievement) -> String {
+    format!(
+        "🏆 <@{}> earned **{}**: {}",
+        discord_id, achievement.name, achievement.description
+    )
+}
+
+/// Count the activity towards the user's achievements, returning a line for each one it earned
+pub async fn award(pool: &PgPool, user: &User, activity: &Activity) -> Vec<String> {
+    utils::log_failure(
+        Achievement::record(pool, user.id, activity).await,
+        &format!("record the achievements of user {}", user.discord_id),
+    )
+    .unwrap_or_default()
+    .iter()
+    .map(|achievement| format_earned(user.discord_id, achievement))
+    .collect()
+}
+
+fn format_achievement(achievement: &AchievementProgress) -> String {
+    if achievement.earned {
+        format!("🏆 **{}**: {}", achievement.name, achievement.description)
+    } else {
+        format!(
+            "**{}**: {} ({} of {})",
+            achievement.name,
+            achievement.description,
+            // the target of a whole cycle can shrink if scenarios are moved out of it
+            (achievement.progress as i64).min(achievement.target),
+            achievement.target
+        )
+    }
+}
+
+#[command]
+#[max_args(1)]
+#[usage = "[@user]"]
+#[example = ""]
+#[example = "@Frodo"]
+/// Display your achievements, or someone else's
+pub async fn profile(ctx: &Context, msg: &Message) -> CommandResult {
+    let discord_user = msg.mentions.first().unwrap_or(&msg.author);
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let user = User::find_or_create(pool, discord_user.id.as_u64(), &discord_user.name).await?;
+    let achievements = Achievement::find_progress_by_user(pool, user.id).await?;
+    let earned = achievements
+        .iter()
+        .filter(|achievement| achievement.earned)
+        .count();
+
+    let mut lines = vec![format!(
+        "{} of {} achievements earned.",
+        earned,
+        achievements.len()
+    )];
+    if !achievements.is_empty() {
+        lines.push(String::new());
+        lines.extend(achievements.iter().map(format_achievement));
+    }
+    let card = Card {
+        lines,
+        ..Card::new(format!("{}'s Profile", discord_user.name))
+    };
+    embed::send_card(ctx, pool, msg, &card).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_formats_achievements() {
+        let achievement = |progress, earned| AchievementProgress {
+            name: "Gauntlet Survivor".to_string(),
+            description: "Survive 10 gauntlets.".to_string(),
+            progress,
+            target: 10,
+            earned,
+        };
+
+        assert_eq!(
+            format_achievement(&achievement(3, false)),
+            "**Gauntlet Survivor**: Survive 10 gauntlets. (3 of 10)"
+        );
+        assert_eq!(
+            format_achievement(&achievement(10, true)),
+            "🏆 **Gauntlet Survivor**: Survive 10 gauntlets."
+        );
+        assert_eq!(
+            format_earned(
+                7,
+                &Achievement {
+                    name: "First Blood".to_string(),
+                    description: "Win your first quest.".to_string(),
+                }
+            ),
+            "🏆 <@7> earned **First Blood**: Win your first quest."
+        );
+    }
+}
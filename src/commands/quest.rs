@@ -1,15 +1,84 @@
-use crate::utils::PostgresPool;
+use crate::utils::{self, PostgresPool};
+use draw::DrawOptions;
+use filter::ScenarioFilter;
 use serenity::{
     framework::standard::{macros::command, Args, CommandResult},
     model::channel::Message,
     prelude::Context,
 };
+use sqlx::postgres::PgPool;
+use std::time::Duration;
+
+pub mod draw;
+pub mod filter;
+pub mod vote;
 
 pub const DEFAULT_QUESTS_NUM: i64 = 3;
+/// Sent instead of drawing when the difficulty filter can't match anything
+pub const UNRATED_DIFFICULTY: &str =
+    "No scenarios have a difficulty rating yet, so they can't be filtered by difficulty.";
+
+/// Parse the draw options and scenario filters out of the remaining command arguments
+pub fn parse_draw_args(
+    input: &str,
+    quantity: usize,
+) -> anyhow::Result<(ScenarioFilter, DrawOptions)> {
+    parse_draw_tokens(filter::tokenize(input), quantity)
+}
+
+/// Parse the draw options and scenario filters out of already tokenized arguments
+pub fn parse_draw_tokens(
+    mut tokens: Vec<String>,
+    quantity: usize,
+) -> anyhow::Result<(ScenarioFilter, DrawOptions)> {
+    let options = DrawOptions::take(&mut tokens)?;
+    let filter = ScenarioFilter::from_tokens(tokens)?;
+
+    if options.vote.is_some() && quantity > vote::VOTE_EMOJIS.len() {
+        return Err(anyhow::anyhow!(
+            "Only up to {} quests can be voted on.",
+            vote::VOTE_EMOJIS.len()
+        ));
+    }
+
+    Ok((filter, options))
+}
+
+/// Parse `[quest] [filters]` for something that can be paired with a quest, returning whether to
+/// draw the quest. `drawn` names what's drawn alongside it for errors, i.e. "Gauntlets".
+pub fn parse_paired_tokens(
+    mut quest: bool,
+    mut tokens: Vec<String>,
+    drawn: &str,
+) -> anyhow::Result<(bool, ScenarioFilter, DrawOptions)> {
+    tokens.retain(|token| {
+        if token.eq_ignore_ascii_case("quest") {
+            quest = true;
+            false
+        } else {
+            true
+        }
+    });
+    let options = DrawOptions::take(&mut tokens)?;
+    let filter = ScenarioFilter::from_tokens(tokens)?;
+
+    if options.vote.is_some() {
+        return Err(anyhow::anyhow!("{} can't be voted on.", drawn));
+    }
+    if !quest && (!filter.is_empty() || options.weighted) {
+        return Err(anyhow::anyhow!(
+            "Quest filters can only be used along with 'quest'."
+        ));
+    }
+
+    Ok((quest, filter, options))
+}
 
 pub struct Scenario {
+    pub id: i64,
     pub title: String,
     pub set_name: String,
+    pub code: String,
 }
 
 pub fn format_quests_display(scenarios: &Vec<Scenario>) -> String {
@@ -28,35 +97,200 @@ pub fn format_quests_display(scenarios: &Vec<Scenario>) -> String {
         .join("\n")
 }
 
+/// Format the drawn quests along with the seed that draws them again
+pub fn format_draw(scenarios: &Vec<Scenario>, seed: &str) -> String {
+    format!("{}\nSeed: `{}`", format_quests_display(scenarios), seed)
+}
+
+/// Announce the quest that won the vote
+pub fn format_vote(winner: Option<&Scenario>) -> String {
+    match winner {
+        Some(scenario) => format!(
+            "The vote is in: **{}** from {}",
+            scenario.title, scenario.set_name
+        ),
+        None => "No votes were cast.".to_string(),
+    }
+}
+
+/// Whether the filter asks for a difficulty when no scenario has been rated yet, so nothing could
+/// match it
+pub async fn is_unrated_difficulty(pool: &PgPool, filter: &ScenarioFilter) -> anyhow::Result<bool> {
+    if filter.min_difficulty.is_none() && filter.max_difficulty.is_none() {
+        return Ok(false);
+    }
+
+    Ok(!sqlx::query!(
+        r#"
+SELECT EXISTS (
+    SELECT 1
+    FROM scenarios
+    WHERE scenarios.difficulty IS NOT NULL
+) AS "rated!"
+"#
+    )
+    .fetch_one(pool)
+    .await?
+    .rated)
+}
+
+/// Find every scenario matching the filters along with how many times it's been played, ordered by
+/// code so seeded draws are reproducible
+pub async fn find_candidates(
+    pool: &PgPool,
+    filter: &ScenarioFilter,
+    author_id: u64,
+) -> anyhow::Result<Vec<(Scenario, i64)>> {
+    find_matching(pool, None, filter, author_id).await
+}
+
+/// Find the event's scenarios that are still incomplete and not checked out, matching the filters
+pub async fn find_event_candidates(
+    pool: &PgPool,
+    event_id: i64,
+    filter: &ScenarioFilter,
+    author_id: u64,
+) -> anyhow::Result<Vec<(Scenario, i64)>> {
+    find_matching(pool, Some(event_id), filter, author_id).await
+}
+
+async fn find_matching(
+    pool: &PgPool,
+    event_id: Option<i64>,
+    filter: &ScenarioFilter,
+    author_id: u64,
+) -> anyhow::Result<Vec<(Scenario, i64)>> {
+    Ok(sqlx::query!(
+        r#"
+SELECT scenarios.id, scenarios.title, sets.name AS set_name, scenarios.code, (
+    SELECT COUNT(*)
+    FROM plays
+    WHERE plays.scenario_id = scenarios.id
+) + (
+    SELECT COUNT(*)
+    FROM events_scenarios
+    WHERE events_scenarios.scenario_id = scenarios.id
+        AND events_scenarios.complete = true
+) AS "plays!"
+FROM scenarios, sets
+WHERE scenarios.set_id = sets.id
+    AND ($1::BIGINT IS NULL OR scenarios.id IN (
+        SELECT events_scenarios.scenario_id
+        FROM events_scenarios
+        WHERE events_scenarios.event_id = $1
+            AND events_scenarios.complete = false
+            AND (events_scenarios.checkout IS NULL OR events_scenarios.checkout < CURRENT_TIMESTAMP - INTERVAL '2 hours')
+    ))
+    AND ($2::VARCHAR IS NULL OR sets.product_type = $2)
+    AND ($3::VARCHAR IS NULL OR LOWER(sets.cycle) = LOWER($3))
+    AND ($4::SMALLINT IS NULL OR scenarios.difficulty >= $4)
+    AND ($5::SMALLINT IS NULL OR scenarios.difficulty <= $5)
+    AND ($6::VARCHAR IS NULL OR LOWER(sets.name) = LOWER($6))
+    AND (sets.product_type IS NULL OR sets.product_type <> ALL($7::VARCHAR[]))
+    AND (NOT $8::BOOLEAN OR scenarios.id NOT IN (
+        SELECT plays.scenario_id
+        FROM plays, users
+        WHERE plays.user_id = users.id
+            AND users.discord_id = $9
+            AND plays.victory = true
+    ))
+    AND (NOT $10::BOOLEAN OR scenarios.set_id IN (
+        SELECT set_id
+        FROM sets_owned_by_all($11)
+    ))
+ORDER BY scenarios.code
+"#,
+        event_id,
+        filter.product_type_str(),
+        filter.cycle,
+        filter.min_difficulty,
+        filter.max_difficulty,
+        filter.set,
+        &filter.exclude_strs(),
+        filter.unbeaten,
+        author_id as i64,
+        filter.owned,
+        &filter.owner_ids(author_id),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| {
+        (
+            Scenario {
+                id: row.id,
+                title: row.title,
+                set_name: row.set_name,
+                code: row.code,
+            },
+            row.plays,
+        )
+    })
+    .collect())
+}
+
 #[command]
-#[usage = "<quantity=default:3>"]
+#[usage = "<quantity=default:3> [filters]"]
 #[example = ""]
 #[example = "5"]
+#[example = "3 set:\"Angmar Awakened\" cycle:dwarrowdelf exclude:saga difficulty:<=5"]
+#[example = "3 unbeaten weighted"]
+#[example = "3 seed:finals"]
+#[example = "3 vote:120"]
 /// Return a list of random quests to tackle. The list quantity defaults to 3.
+/// The quests can be filtered by set, product type, cycle and difficulty, and `unbeaten` leaves
+/// out the quests you've already logged as beaten. `weighted` favors the least played quests.
+/// The same `seed` with the same filters always draws the same quests. `vote` collects votes on
+/// the quests with reactions and announces the winner.
 pub async fn quest(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let quantity = args.single::<i64>().unwrap_or(DEFAULT_QUESTS_NUM);
+    let quantity = args
+        .single::<usize>()
+        .unwrap_or(DEFAULT_QUESTS_NUM as usize);
+    let (filter, options) = match parse_draw_args(args.rest(), quantity) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            msg.channel_id.say(&ctx.http, err.to_string()).await?;
+
+            return Ok(());
+        }
+    };
     let data = ctx.data.read().await;
     let pool = data
         .get::<PostgresPool>()
         .expect("Expected PostgresPool in TypeMap.");
 
-    let scenarios = sqlx::query_as!(
-        Scenario,
-        r#"
-SELECT scenarios.title, sets.name AS set_name
-FROM scenarios, sets
-WHERE scenarios.set_id = sets.id
-ORDER BY RANDOM()
-LIMIT $1;
-"#,
-        quantity
-    )
-    .fetch_all(pool)
-    .await?;
+    if is_unrated_difficulty(pool, &filter).await? {
+        msg.channel_id.say(&ctx.http, UNRATED_DIFFICULTY).await?;
+
+        return Ok(());
+    }
 
-    msg.channel_id
-        .say(&ctx.http, format_quests_display(&scenarios))
-        .await?;
+    let candidates = find_candidates(pool, &filter, *msg.author.id.as_u64()).await?;
+    let seed = options.seed_or(None);
+    let scenarios = draw::draw(candidates, quantity, &options, &mut draw::seeded_rng(&seed));
+
+    if scenarios.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "Could not find any quests.")
+            .await?;
+    } else {
+        let message =
+            utils::say_paginated(ctx, msg.channel_id, &[format_draw(&scenarios, &seed)]).await?;
+
+        if let (Some(message), Some(window)) =
+            (message, options.vote.filter(|_| scenarios.len() > 1))
+        {
+            let winner =
+                vote::collect_votes(ctx, &message, scenarios.len(), Duration::from_secs(window))
+                    .await?;
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format_vote(winner.and_then(|index| scenarios.get(index))),
+                )
+                .await?;
+        }
+    }
 
     Ok(())
 }
@@ -69,16 +303,22 @@ mod tests {
     fn it_formats_the_scenarios() {
         let scenarios = vec![
             Scenario {
+                id: 1,
                 title: "Foo".to_string(),
                 set_name: "Core".to_string(),
+                code: "0101".to_string(),
             },
             Scenario {
+                id: 2,
                 title: "Bar".to_string(),
                 set_name: "Core".to_string(),
+                code: "0102".to_string(),
             },
             Scenario {
+                id: 3,
                 title: "Baz".to_string(),
                 set_name: "Core".to_string(),
+                code: "0103".to_string(),
             },
         ];
 
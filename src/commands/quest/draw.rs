@@ -0,0 +1,250 @@
+//! Random draws over a list of candidates that have already been filtered by SQL
+use super::vote::{DEFAULT_VOTE_WINDOW, MAX_VOTE_WINDOW};
+use rand::{distributions::Alphanumeric, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Length of the seeds generated when none is given
+const SEED_LEN: usize = 8;
+
+/// Options for how quests are drawn, parsed alongside the scenario filters
+#[derive(Debug, Default, PartialEq)]
+pub struct DrawOptions {
+    /// Favor the scenarios that have been played the least
+    pub weighted: bool,
+    /// Seed for a reproducible draw
+    pub seed: Option<String>,
+    /// Seconds to collect votes on the drawn quests for
+    pub vote: Option<u64>,
+}
+
+impl DrawOptions {
+    /// Pull the draw options out of the tokens, leaving the filters behind
+    pub fn take(tokens: &mut Vec<String>) -> anyhow::Result<Self> {
+        let mut options = DrawOptions::default();
+        let mut vote_window = None;
+
+        tokens.retain(|token| {
+            if token.eq_ignore_ascii_case("weighted") {
+                options.weighted = true;
+                false
+            } else if token.eq_ignore_ascii_case("vote") {
+                options.vote = Some(DEFAULT_VOTE_WINDOW);
+                false
+            } else if let Some(window) = token
+                .split_once(':')
+                .filter(|(key, _)| key.eq_ignore_ascii_case("vote"))
+                .map(|(_, window)| window.to_string())
+            {
+                vote_window = Some(window);
+                false
+            } else if let Some(seed) = token
+                .split_once(':')
+                .filter(|(key, _)| key.eq_ignore_ascii_case("seed"))
+                .map(|(_, seed)| seed.to_string())
+            {
+                options.seed = Some(seed);
+                false
+            } else {
+                true
+            }
+        });
+
+        if options.seed.as_deref() == Some("") {
+            return Err(anyhow::anyhow!("The seed can't be empty."));
+        }
+        if let Some(window) = vote_window {
+            options.vote = Some(
+                window
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|window| (1..=MAX_VOTE_WINDOW).contains(window))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "The vote window must be from 1 to {} seconds.",
+                            MAX_VOTE_WINDOW
+                        )
+                    })?,
+            );
+        }
+
+        Ok(options)
+    }
+
+    /// The seed given, falling back to `default` and then a freshly generated one, so that
+    /// every draw can be repeated
+    pub fn seed_or(&self, default: Option<&str>) -> String {
+        self.seed
+            .as_deref()
+            .or(default)
+            .map(str::to_string)
+            .unwrap_or_else(generate_seed)
+    }
+}
+
+/// Generate a short random seed that's easy to copy
+pub fn generate_seed() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(SEED_LEN)
+        .map(char::from)
+        .collect()
+}
+
+/// A random number generator that always produces the same numbers for the same seed, regardless
+/// of platform or version. The seed is hashed with FNV-1a since `std`'s hasher isn't stable.
+pub fn seeded_rng(seed: &str) -> ChaCha8Rng {
+    let hash = seed.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    ChaCha8Rng::seed_from_u64(hash)
+}
+
+/// Weight for a scenario given how many times it's been played or completed. Scenarios that have
+/// never been played are the most likely to be drawn.
+pub fn play_weight(plays: i64) -> f64 {
+    1.0 / (1 + plays.max(0)) as f64
+}
+
+/// Draw up to `quantity` distinct items, each pick proportional to its weight
+pub fn weighted_sample<T, R: Rng + ?Sized>(
+    mut items: Vec<(T, f64)>,
+    quantity: usize,
+    rng: &mut R,
+) -> Vec<T> {
+    let mut drawn = Vec::new();
+
+    while drawn.len() < quantity && !items.is_empty() {
+        let total: f64 = items.iter().map(|(_, weight)| weight).sum();
+        let mut target = rng.gen::<f64>() * total;
+        let index = items
+            .iter()
+            .position(|(_, weight)| {
+                if target < *weight {
+                    true
+                } else {
+                    target -= weight;
+                    false
+                }
+            })
+            // guard against floating point error on the last item
+            .unwrap_or(items.len() - 1);
+
+        drawn.push(items.remove(index).0);
+    }
+
+    drawn
+}
+
+/// Draw up to `quantity` distinct candidates, weighting by plays when asked to
+pub fn draw<T, R: Rng + ?Sized>(
+    candidates: Vec<(T, i64)>,
+    quantity: usize,
+    options: &DrawOptions,
+    rng: &mut R,
+) -> Vec<T> {
+    let weighted = candidates
+        .into_iter()
+        .map(|(candidate, plays)| {
+            let weight = if options.weighted {
+                play_weight(plays)
+            } else {
+                1.0
+            };
+
+            (candidate, weight)
+        })
+        .collect();
+
+    weighted_sample(weighted, quantity, rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn it_draws_distinct_items() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut drawn = weighted_sample(vec![("a", 1.0), ("b", 1.0), ("c", 1.0)], 5, &mut rng);
+        drawn.sort_unstable();
+
+        assert_eq!(drawn, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn it_favors_the_least_played() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let options = DrawOptions {
+            weighted: true,
+            ..Default::default()
+        };
+        let fresh = (0..1000)
+            .filter(|_| draw(vec![("fresh", 0), ("stale", 9)], 1, &options, &mut rng) == ["fresh"])
+            .count();
+
+        // the fresh scenario has 10x the weight, so it should be drawn ~91% of the time
+        assert!(fresh > 850, "fresh was only drawn {} times", fresh);
+    }
+
+    #[test]
+    fn it_takes_the_draw_options() {
+        let mut tokens = vec![
+            "cycle:dwarrowdelf".to_string(),
+            "Weighted".to_string(),
+            "seed:finals".to_string(),
+            "vote:30".to_string(),
+        ];
+
+        assert_eq!(
+            DrawOptions::take(&mut tokens).unwrap(),
+            DrawOptions {
+                weighted: true,
+                seed: Some("finals".to_string()),
+                vote: Some(30),
+            }
+        );
+        assert_eq!(tokens, vec!["cycle:dwarrowdelf"]);
+        assert_eq!(
+            DrawOptions::take(&mut vec!["vote".to_string()])
+                .unwrap()
+                .vote,
+            Some(DEFAULT_VOTE_WINDOW)
+        );
+        assert!(DrawOptions::take(&mut vec!["seed:".to_string()]).is_err());
+        assert!(DrawOptions::take(&mut vec!["vote:0".to_string()]).is_err());
+    }
+
+    #[test]
+    fn it_draws_the_same_quests_for_the_same_seed() {
+        let candidates = || (0..50).map(|n| (n, n as i64 % 3)).collect::<Vec<_>>();
+        let options = DrawOptions {
+            weighted: true,
+            ..Default::default()
+        };
+        let first = draw(candidates(), 3, &options, &mut seeded_rng("finals"));
+
+        assert_eq!(
+            first,
+            draw(candidates(), 3, &options, &mut seeded_rng("finals"))
+        );
+        assert_ne!(
+            first,
+            draw(candidates(), 3, &options, &mut seeded_rng("semifinals"))
+        );
+    }
+
+    #[test]
+    fn it_falls_back_to_the_default_seed() {
+        let options = DrawOptions::default();
+        assert_eq!(options.seed_or(Some("event")), "event");
+        assert_eq!(options.seed_or(None).len(), SEED_LEN);
+
+        let options = DrawOptions {
+            seed: Some("table".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(options.seed_or(Some("event")), "table");
+    }
+}
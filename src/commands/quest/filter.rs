@@ -0,0 +1,227 @@
+//! Filters for narrowing down the scenario pool shared by the quest commands, e.g.
+//! `set:"Angmar Awakened" cycle:dwarrowdelf exclude:saga difficulty:<=5 unbeaten owned:@a @b`
+use crate::models::set::ProductType;
+use serenity::utils::parse_username;
+
+/// Scenario filters parsed from `key:value` arguments
+#[derive(Debug, Default, PartialEq)]
+pub struct ScenarioFilter {
+    pub set: Option<String>,
+    pub product_type: Option<ProductType>,
+    pub exclude: Vec<ProductType>,
+    pub cycle: Option<String>,
+    pub min_difficulty: Option<i16>,
+    pub max_difficulty: Option<i16>,
+    /// Leave out scenarios the caller has already logged as beaten
+    pub unbeaten: bool,
+    /// Only include scenarios from sets in everyone's collection
+    pub owned: bool,
+    /// Discord ids of the mentioned owners. When empty, this is the caller.
+    pub owners: Vec<u64>,
+}
+
+impl ScenarioFilter {
+    /// Parse filters out of the remaining command arguments
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        Self::from_tokens(tokenize(input))
+    }
+
+    /// Parse filters out of already tokenized arguments
+    pub fn from_tokens(tokens: Vec<String>) -> anyhow::Result<Self> {
+        let mut filter = ScenarioFilter::default();
+        // mentions are only valid right after `owned:`
+        let mut parsing_owners = false;
+
+        for token in tokens {
+            if let Some(owner) = parse_username(&token) {
+                if parsing_owners {
+                    filter.owners.push(owner);
+                    continue;
+                }
+
+                return Err(anyhow::anyhow!("Mentions must follow 'owned:'."));
+            }
+            parsing_owners = false;
+
+            if token.eq_ignore_ascii_case("unbeaten") {
+                filter.unbeaten = true;
+                continue;
+            } else if token.eq_ignore_ascii_case("owned") {
+                filter.owned = true;
+                continue;
+            }
+
+            let (key, value) = token
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("Unknown filter: {}", token))?;
+            match key.to_lowercase().as_str() {
+                "set" => filter.set = Some(value.to_string()),
+                "type" => filter.product_type = Some(value.parse()?),
+                "exclude" => {
+                    for product_type in value.split(',') {
+                        filter.exclude.push(product_type.parse()?);
+                    }
+                }
+                "cycle" => filter.cycle = Some(value.to_string()),
+                "difficulty" => {
+                    let (min, max) = parse_difficulty(value)?;
+                    filter.min_difficulty = min;
+                    filter.max_difficulty = max;
+                }
+                "owned" => {
+                    filter.owned = true;
+                    parsing_owners = true;
+                    if !value.is_empty() {
+                        filter.owners.push(
+                            parse_username(value)
+                                .ok_or_else(|| anyhow::anyhow!("Not a mention: {}", value))?,
+                        );
+                    }
+                }
+                _ => return Err(anyhow::anyhow!("Unknown filter: {}", token)),
+            }
+        }
+
+        Ok(filter)
+    }
+
+    /// No filters were given
+    pub fn is_empty(&self) -> bool {
+        *self == ScenarioFilter::default()
+    }
+
+    /// The product type as it's stored in the database
+    pub fn product_type_str(&self) -> Option<&'static str> {
+        self.product_type.map(|product_type| product_type.as_str())
+    }
+
+    /// Discord ids of everyone who must own the scenario's set, defaulting to the caller
+    pub fn owner_ids(&self, author_id: u64) -> Vec<i64> {
+        let mut owners = if self.owners.is_empty() {
+            vec![author_id as i64]
+        } else {
+            self.owners.iter().map(|owner| *owner as i64).collect()
+        };
+        owners.sort_unstable();
+        owners.dedup();
+
+        owners
+    }
+
+    /// The excluded product types as they're stored in the database
+    pub fn exclude_strs(&self) -> Vec<String> {
+        self.exclude
+            .iter()
+            .map(|product_type| product_type.as_str().to_string())
+            .collect()
+    }
+}
+
+/// Parse a difficulty comparison like `5`, `<=5` or `>3` into an inclusive (min, max) range
+fn parse_difficulty(value: &str) -> anyhow::Result<(Option<i16>, Option<i16>)> {
+    let (operator, number) = match value.find(|c: char| c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => ("", value),
+    };
+    let number = number
+        .parse::<i16>()
+        .ok()
+        .filter(|number| (1..=10).contains(number))
+        .ok_or_else(|| anyhow::anyhow!("Difficulty must be a number from 1 to 10."))?;
+
+    match operator {
+        "" | "=" => Ok((Some(number), Some(number))),
+        "<" => Ok((None, Some(number - 1))),
+        "<=" => Ok((None, Some(number))),
+        ">" => Ok((Some(number + 1), None)),
+        ">=" => Ok((Some(number), None)),
+        _ => Err(anyhow::anyhow!(
+            "Difficulty comparisons must be one of '<', '<=', '=', '>=', '>'."
+        )),
+    }
+}
+
+/// Split arguments on whitespace while keeping double quoted values together, so
+/// `set:"Angmar Awakened"` is a single token.
+pub fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' | '“' | '”' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_tokenizes_quoted_values() {
+        assert_eq!(
+            tokenize(r#"set:"Angmar Awakened"  type:deluxe"#),
+            vec!["set:Angmar Awakened", "type:deluxe"]
+        );
+    }
+
+    #[test]
+    fn it_parses_filters() {
+        assert_eq!(
+            ScenarioFilter::parse(
+                r#"set:"Angmar Awakened" cycle:dwarrowdelf exclude:saga,starter difficulty:<=5 unbeaten"#
+            )
+            .unwrap(),
+            ScenarioFilter {
+                set: Some("Angmar Awakened".to_string()),
+                product_type: None,
+                exclude: vec![ProductType::Saga, ProductType::Starter],
+                cycle: Some("dwarrowdelf".to_string()),
+                min_difficulty: None,
+                max_difficulty: Some(5),
+                unbeaten: true,
+                owned: false,
+                owners: vec![],
+            }
+        );
+        assert!(ScenarioFilter::parse("").unwrap().is_empty());
+        assert!(ScenarioFilter::parse("colour:purple").is_err());
+        assert!(ScenarioFilter::parse("exclude:expansion").is_err());
+    }
+
+    #[test]
+    fn it_parses_owners() {
+        let filter = ScenarioFilter::parse("owned").unwrap();
+        assert!(filter.owned);
+        assert_eq!(filter.owner_ids(1), vec![1]);
+
+        let filter = ScenarioFilter::parse("owned:<@2> <@!3> <@2> unbeaten").unwrap();
+        assert!(filter.owned && filter.unbeaten);
+        assert_eq!(filter.owner_ids(1), vec![2, 3]);
+
+        assert!(ScenarioFilter::parse("unbeaten <@2>").is_err());
+    }
+
+    #[test]
+    fn it_parses_difficulty_comparisons() {
+        assert_eq!(parse_difficulty("5").unwrap(), (Some(5), Some(5)));
+        assert_eq!(parse_difficulty("<5").unwrap(), (None, Some(4)));
+        assert_eq!(parse_difficulty(">=7").unwrap(), (Some(7), None));
+        assert!(parse_difficulty("hard").is_err());
+        assert!(parse_difficulty("<=11").is_err());
+        assert!(parse_difficulty("=<5").is_err());
+    }
+}
@@ -0,0 +1,133 @@
+//! Voting on drawn quests with numbered reactions
+use serenity::{
+    collector::ReactionAction,
+    futures::StreamExt,
+    model::{channel::Message, channel::ReactionType, id::UserId},
+    prelude::Context,
+};
+use std::{collections::HashSet, time::Duration};
+
+/// Reactions used to vote, in the order the quests are listed
+pub const VOTE_EMOJIS: [&str; 10] = [
+    "1\u{fe0f}\u{20e3}",
+    "2\u{fe0f}\u{20e3}",
+    "3\u{fe0f}\u{20e3}",
+    "4\u{fe0f}\u{20e3}",
+    "5\u{fe0f}\u{20e3}",
+    "6\u{fe0f}\u{20e3}",
+    "7\u{fe0f}\u{20e3}",
+    "8\u{fe0f}\u{20e3}",
+    "9\u{fe0f}\u{20e3}",
+    "\u{1f51f}",
+];
+
+/// Seconds to collect votes for when no window is given
+pub const DEFAULT_VOTE_WINDOW: u64 = 60;
+/// Longest a vote can stay open, in seconds
+pub const MAX_VOTE_WINDOW: u64 = 900;
+
+/// The option a reaction votes for, if it's one of the vote emojis
+fn vote_index(emoji: &ReactionType, options: usize) -> Option<usize> {
+    match emoji {
+        ReactionType::Unicode(emoji) => VOTE_EMOJIS
+            .iter()
+            .take(options)
+            .position(|vote| vote == emoji),
+        _ => None,
+    }
+}
+
+/// The option with the most votes. Ties go to the option listed first.
+pub fn tally(votes: &HashSet<(UserId, usize)>, options: usize) -> Option<usize> {
+    let mut counts = vec![0; options];
+    for (_, index) in votes {
+        if let Some(count) = counts.get_mut(*index) {
+            *count += 1;
+        }
+    }
+
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        // max_by_key returns the last max, so compare reversed indexes to keep the first
+        .max_by_key(|(index, count)| (**count, std::cmp::Reverse(*index)))
+        .map(|(index, _)| index)
+}
+
+/// Add a numbered reaction for each option to the message and collect votes for `window`.
+/// Returns the index of the winning option, or `None` when nobody voted.
+pub async fn collect_votes(
+    ctx: &Context,
+    message: &Message,
+    options: usize,
+    window: Duration,
+) -> anyhow::Result<Option<usize>> {
+    let options = options.min(VOTE_EMOJIS.len());
+    let bot_id = ctx.cache.current_user_id().await;
+    // start collecting before reacting so early votes aren't missed
+    let mut collector = message
+        .await_reactions(&ctx)
+        .added(true)
+        .removed(true)
+        .timeout(window)
+        .await;
+
+    for emoji in VOTE_EMOJIS.iter().take(options) {
+        message
+            .react(&ctx.http, ReactionType::Unicode(emoji.to_string()))
+            .await?;
+    }
+
+    let mut votes = HashSet::new();
+    while let Some(action) = collector.next().await {
+        let reaction = action.as_inner_ref();
+        let user_id = match reaction.user_id {
+            Some(user_id) if user_id != bot_id => user_id,
+            _ => continue,
+        };
+
+        if let Some(index) = vote_index(&reaction.emoji, options) {
+            match *action {
+                ReactionAction::Added(_) => votes.insert((user_id, index)),
+                ReactionAction::Removed(_) => votes.remove(&(user_id, index)),
+            };
+        }
+    }
+
+    Ok(tally(&votes, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_tallies_the_votes() {
+        let votes = [(1, 0), (2, 1), (3, 1), (1, 2)]
+            .iter()
+            .map(|(user, index)| (UserId(*user), *index))
+            .collect();
+        assert_eq!(tally(&votes, 3), Some(1));
+
+        let votes = [(1, 2), (2, 1)]
+            .iter()
+            .map(|(user, index)| (UserId(*user), *index))
+            .collect();
+        assert_eq!(tally(&votes, 3), Some(1));
+
+        assert_eq!(tally(&HashSet::new(), 3), None);
+    }
+
+    #[test]
+    fn it_only_counts_the_listed_options() {
+        let emoji = |i: usize| ReactionType::Unicode(VOTE_EMOJIS[i].to_string());
+
+        assert_eq!(vote_index(&emoji(1), 3), Some(1));
+        assert_eq!(vote_index(&emoji(4), 3), None);
+        assert_eq!(
+            vote_index(&ReactionType::Unicode("👍".to_string()), 3),
+            None
+        );
+    }
+}
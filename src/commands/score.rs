@@ -0,0 +1,161 @@
+//! Collection of commands for the official LOTR LCG scoring
+use crate::{
+    models::{
+        event::Event,
+        scenario::Scenario,
+        score::{Score, ScoreSheet},
+        user::User,
+    },
+    utils::{self, PostgresPool},
+};
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::channel::Message,
+    prelude::Context,
+};
+
+const LEADERBOARD_SIZE: i64 = 10;
+
+#[command]
+#[num_args(1)]
+#[usage = "<scenario code|alias>"]
+#[example = "0101"]
+/// Calculate the official score for a game and submit it. If there's an active event, the score
+/// is submitted to it.
+pub async fn score(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let code = args.single_quoted::<String>()?;
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let scenario = match Scenario::find_by_alias_or_code(pool, &code).await? {
+        Some(scenario) => scenario,
+        None => {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!("No scenario found by that code or alias: {}", code),
+                )
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let mut sheet = ScoreSheet::default();
+    for (question, value) in [
+        ("What was your final threat?", &mut sheet.final_threat),
+        (
+            "What was the total threat cost of your dead heroes?",
+            &mut sheet.dead_heroes_threat,
+        ),
+        ("How much damage was on your heroes?", &mut sheet.damage),
+        ("How many rounds did you play?", &mut sheet.rounds),
+        (
+            "How many victory points did you earn?",
+            &mut sheet.victory_points,
+        ),
+    ] {
+        match utils::ask_number(ctx, msg, question).await? {
+            Some(number) if number >= 0 => *value = number,
+            _ => {
+                msg.channel_id
+                    .say(&ctx.http, "Not a valid number. Score was not submitted.")
+                    .await?;
+
+                return Ok(());
+            }
+        }
+    }
+
+    let event = Event::find_by_active(pool, true).await?;
+    let user = User::find_or_create(pool, msg.author.id.as_u64(), &msg.author.name).await?;
+    Score::create(
+        pool,
+        user.id,
+        scenario.id,
+        event.as_ref().map(|event| event.id),
+        &sheet,
+    )
+    .await?;
+
+    msg.reply(
+        &ctx.http,
+        format!(
+            "You scored **{}** on **{}**.",
+            sheet.score(),
+            scenario.title
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[num_args(1)]
+#[usage = "<scenario code|alias>"]
+#[example = "0101"]
+#[example = "JtR"]
+/// Display the lowest scores for a quest in the event
+pub async fn best(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let code = args.single_quoted::<String>()?;
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "No active event found.")
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let scenario = match Scenario::find_by_alias_or_code(pool, &code).await? {
+        Some(scenario) => scenario,
+        None => {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!("No scenario found by that code or alias: {}", code),
+                )
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let scores = Score::find_best(pool, event.id, scenario.id, LEADERBOARD_SIZE).await?;
+    if scores.is_empty() {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!("No scores submitted for **{}** yet.", scenario.title),
+            )
+            .await?;
+    } else {
+        let mut lines = vec![format!("Best scores for **{}**:", scenario.title)];
+        lines.extend(utils::number_collection(
+            &scores
+                .iter()
+                .map(|score| {
+                    format!(
+                        "**{}** by {} in {} rounds",
+                        score.score,
+                        score.name.as_deref().unwrap_or("Unknown"),
+                        score.rounds
+                    )
+                })
+                .collect::<Vec<String>>(),
+        ));
+        utils::say_paginated(ctx, msg.channel_id, &lines).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,937 @@
+//! Application (slash) commands registered alongside the prefix commands. They take typed options
+//! and hand off to the same logic as the prefix commands.
+use crate::{
+    commands::{
+        challenge::{self, gauntlet::GauntletArgs, DEFAULT_MODIFIERS_NUM},
+        event::{self, group_scenarios, kang::KANG_ATTRIBUTE, milestones, rewards},
+        quest::{self, draw, vote},
+    },
+    embed::{self, Card},
+    fuzzy,
+    models::{event::Event, scenario::Scenario},
+    utils::{self, PostgresPool},
+};
+use serenity::{
+    builder::CreateApplicationCommandOption,
+    model::{
+        id::UserId,
+        interactions::{
+            application_command::{
+                ApplicationCommand, ApplicationCommandInteraction,
+                ApplicationCommandInteractionData, ApplicationCommandInteractionDataOption,
+                ApplicationCommandOptionType,
+            },
+            autocomplete::AutocompleteInteraction,
+            InteractionResponseType,
+        },
+        user::User as DiscordUser,
+    },
+    prelude::Context,
+};
+use sqlx::postgres::PgPool;
+use std::time::Duration;
+
+/// Options that narrow down the quest pool or change how quests are drawn, in the order they're
+/// listed. Each one is turned into the same `key:value` argument the prefix commands take.
+const QUEST_OPTIONS: [(&str, &str, ApplicationCommandOptionType); 9] = [
+    (
+        "set",
+        "Only quests from this set",
+        ApplicationCommandOptionType::String,
+    ),
+    (
+        "cycle",
+        "Only quests from this cycle",
+        ApplicationCommandOptionType::String,
+    ),
+    (
+        "type",
+        "Only quests from this product type",
+        ApplicationCommandOptionType::String,
+    ),
+    (
+        "exclude",
+        "Leave out these product types, i.e. saga,standalone",
+        ApplicationCommandOptionType::String,
+    ),
+    (
+        "difficulty",
+        "Only quests of this difficulty, i.e. <=5",
+        ApplicationCommandOptionType::String,
+    ),
+    (
+        "unbeaten",
+        "Leave out the quests you've beaten",
+        ApplicationCommandOptionType::Boolean,
+    ),
+    (
+        "owned",
+        "Only quests from sets in your collection",
+        ApplicationCommandOptionType::Boolean,
+    ),
+    (
+        "weighted",
+        "Favor the least played quests",
+        ApplicationCommandOptionType::Boolean,
+    ),
+    (
+        "seed",
+        "Draw the same quests every time",
+        ApplicationCommandOptionType::String,
+    ),
+];
+
+const PRODUCT_TYPES: [&str; 6] = [
+    "core",
+    "deluxe",
+    "adventure_pack",
+    "saga",
+    "standalone",
+    "starter",
+];
+
+/// The other players attempting a gauntlet or challenge, besides whoever ran the command
+const PLAYER_OPTIONS: [&str; 3] = ["player2", "player3", "player4"];
+
+/// Discord shows at most 25 autocomplete suggestions
+const MAX_SUGGESTIONS: usize = 25;
+/// Discord cuts off suggestion names at 100 characters
+const MAX_SUGGESTION_LENGTH: usize = 100;
+
+fn create_option(
+    kind: ApplicationCommandOptionType,
+    name: &str,
+    description: &str,
+) -> CreateApplicationCommandOption {
+    let mut option = CreateApplicationCommandOption::default();
+    option.kind(kind).name(name).description(description);
+
+    option
+}
+
+/// The quest filters and draw options, optionally along with voting on the quests
+fn quest_options(with_vote: bool) -> Vec<CreateApplicationCommandOption> {
+    let mut options: Vec<CreateApplicationCommandOption> = QUEST_OPTIONS
+        .iter()
+        .map(|(name, description, kind)| {
+            let mut option = create_option(*kind, name, description);
+            if *name == "type" {
+                for product_type in PRODUCT_TYPES {
+                    option.add_string_choice(product_type, product_type);
+                }
+            }
+
+            option
+        })
+        .collect();
+    if with_vote {
+        options.push(create_option(
+            ApplicationCommandOptionType::Integer,
+            "vote",
+            "Vote on the quests with reactions for this many seconds",
+        ));
+    }
+
+    options
+}
+
+fn scenario_option() -> CreateApplicationCommandOption {
+    let mut option = create_option(
+        ApplicationCommandOptionType::String,
+        "scenario",
+        "The scenario's code, alias or title",
+    );
+    option.required(true).set_autocomplete(true);
+
+    option
+}
+
+fn challenge_option() -> CreateApplicationCommandOption {
+    let mut option = create_option(
+        ApplicationCommandOptionType::String,
+        "challenge",
+        "The challenge's code or name",
+    );
+    option.required(true).set_autocomplete(true);
+
+    option
+}
+
+fn player_options(description: &str) -> Vec<CreateApplicationCommandOption> {
+    PLAYER_OPTIONS
+        .iter()
+        .map(|name| create_option(ApplicationCommandOptionType::User, name, description))
+        .collect()
+}
+
+/// Register the slash commands, replacing any that were registered before
+pub async fn register(ctx: &Context) -> anyhow::Result<()> {
+    ApplicationCommand::set_global_application_commands(&ctx.http, |commands| {
+        commands
+            .create_application_command(|command| {
+                command
+                    .name("quest")
+                    .description("Draw random quests to tackle")
+                    .set_options(
+                        std::iter::once(create_option(
+                            ApplicationCommandOptionType::Integer,
+                            "quantity",
+                            "How many quests to draw, 3 by default",
+                        ))
+                        .chain(quest_options(true))
+                        .collect(),
+                    )
+            })
+            .create_application_command(|command| {
+                command
+                    .name("event")
+                    .description("Interact with the active event")
+                    .create_option(|option| {
+                        option
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .name("quest")
+                            .description("Draw quests remaining for the event")
+                            .add_sub_option(create_option(
+                                ApplicationCommandOptionType::Integer,
+                                "quantity",
+                                "How many quests to draw, 3 by default",
+                            ));
+                        for quest_option in quest_options(true) {
+                            option.add_sub_option(quest_option);
+                        }
+
+                        option
+                    })
+                    .create_option(|option| {
+                        option
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .name("complete")
+                            .description("Mark a quest as complete")
+                            .add_sub_option(scenario_option())
+                    })
+                    .create_option(|option| {
+                        option
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .name("checkout")
+                            .description("Reserve a quest for 2 hours")
+                            .add_sub_option(scenario_option())
+                    })
+                    .create_option(|option| {
+                        option
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .name("progress")
+                            .description("Display how much of the event is complete")
+                    })
+                    .create_option(|option| {
+                        option
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .name("conquer")
+                            .description("Conquer one of the Council of 100 Kangs")
+                            .add_sub_option(challenge_option());
+                        for player_option in
+                            player_options("Another player who completed the challenge")
+                        {
+                            option.add_sub_option(player_option);
+                        }
+
+                        option
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("gauntlet")
+                    .description("Build a gauntlet out of random modifiers")
+                    .set_options(
+                        vec![
+                            create_option(
+                                ApplicationCommandOptionType::Integer,
+                                "modifiers",
+                                "How many modifiers to draw, 1 by default",
+                            ),
+                            create_option(
+                                ApplicationCommandOptionType::Boolean,
+                                "quest",
+                                "Pair the gauntlet with a random quest",
+                            ),
+                        ]
+                        .into_iter()
+                        .chain(player_options("Another player attempting the gauntlet"))
+                        .chain(quest_options(false))
+                        .collect(),
+                    )
+            })
+    })
+    .await?;
+
+    Ok(())
+}
+
+fn find_option<'a>(
+    options: &'a [ApplicationCommandInteractionDataOption],
+    name: &str,
+) -> Option<&'a ApplicationCommandInteractionDataOption> {
+    options.iter().find(|option| option.name == name)
+}
+
+fn integer_option(options: &[ApplicationCommandInteractionDataOption], name: &str) -> Option<i64> {
+    find_option(options, name)
+        .and_then(|option| option.value.as_ref())
+        .and_then(|value| value.as_i64())
+}
+
+fn string_option<'a>(
+    options: &'a [ApplicationCommandInteractionDataOption],
+    name: &str,
+) -> Option<&'a str> {
+    find_option(options, name)
+        .and_then(|option| option.value.as_ref())
+        .and_then(|value| value.as_str())
+}
+
+fn bool_option(options: &[ApplicationCommandInteractionDataOption], name: &str) -> bool {
+    find_option(options, name)
+        .and_then(|option| option.value.as_ref())
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+/// The players given with the player options, after whoever ran the command
+fn players<'a>(
+    user: &'a DiscordUser,
+    data: &'a ApplicationCommandInteractionData,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> Vec<&'a DiscordUser> {
+    let mut players = vec![user];
+    players.extend(PLAYER_OPTIONS.iter().filter_map(|name| {
+        string_option(options, name)
+            .and_then(|id| id.parse::<u64>().ok())
+            .and_then(|id| data.resolved.users.get(&UserId(id)))
+    }));
+
+    players
+}
+
+/// How many items to draw, falling back to the default when it's left out or negative
+fn quantity_option(
+    options: &[ApplicationCommandInteractionDataOption],
+    name: &str,
+    default: usize,
+) -> usize {
+    integer_option(options, name)
+        .and_then(|quantity| usize::try_from(quantity).ok())
+        .unwrap_or(default)
+}
+
+/// Turn the quest options that were given into the tokens the prefix commands parse, i.e.
+/// `cycle:dwarrowdelf` or `unbeaten`
+pub fn quest_tokens(options: &[ApplicationCommandInteractionDataOption]) -> Vec<String> {
+    options
+        .iter()
+        .filter(|option| {
+            option.name == "vote" || QUEST_OPTIONS.iter().any(|(name, ..)| *name == option.name)
+        })
+        .filter_map(|option| {
+            let value = option.value.as_ref()?;
+            match value.as_bool() {
+                Some(true) => Some(option.name.clone()),
+                Some(false) => None,
+                None => Some(format!(
+                    "{}:{}",
+                    option.name,
+                    value
+                        .as_str()
+                        .map(|value| value.to_string())
+                        .unwrap_or_else(|| value.to_string())
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Respond to the command, following up with the rest when it's too long for one message
+async fn respond(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    content: impl ToString,
+) -> anyhow::Result<()> {
+    let mut pages = utils::paginate(&[content.to_string()]).into_iter();
+    let first = pages.next().unwrap_or_default();
+    command
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|data| data.content(first))
+        })
+        .await?;
+    for page in pages {
+        follow_up(ctx, command, page).await?;
+    }
+
+    Ok(())
+}
+
+/// Respond with the card, as plain text when the server has turned embeds off
+async fn respond_card(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &ApplicationCommandInteraction,
+    card: &Card,
+) -> anyhow::Result<()> {
+    if embed::guild_plain_text(pool, command.guild_id).await? {
+        let mut pages = card.to_plain_text().into_iter();
+        respond(ctx, command, pages.next().unwrap_or_default()).await?;
+        for page in pages {
+            follow_up(ctx, command, page).await?;
+        }
+
+        return Ok(());
+    }
+
+    let mut embeds = card.to_embeds().into_iter();
+    let first = embeds.next().unwrap_or_default();
+    command
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|data| data.add_embed(first))
+        })
+        .await?;
+    for embed in embeds {
+        command
+            .create_followup_message(&ctx.http, |message| message.add_embed(embed))
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn follow_up(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    content: impl ToString,
+) -> anyhow::Result<()> {
+    for page in utils::paginate(&[content.to_string()]) {
+        command
+            .create_followup_message(&ctx.http, |message| message.content(page))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Run a slash command
+pub async fn handle(ctx: &Context, command: &ApplicationCommandInteraction) -> anyhow::Result<()> {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+    let options = &command.data.options;
+
+    match command.data.name.as_str() {
+        "quest" => quest(ctx, pool, command, options).await,
+        "event" => match options.first() {
+            Some(subcommand) => match subcommand.name.as_str() {
+                "quest" => event_quest(ctx, pool, command, &subcommand.options).await,
+                "complete" => event_complete(ctx, pool, command, &subcommand.options).await,
+                "checkout" => event_checkout(ctx, pool, command, &subcommand.options).await,
+                "progress" => event_progress(ctx, pool, command).await,
+                "conquer" => event_conquer(ctx, pool, command, &subcommand.options).await,
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        },
+        "gauntlet" => gauntlet(ctx, pool, command, options).await,
+        _ => Ok(()),
+    }
+}
+
+async fn quest(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &ApplicationCommandInteraction,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> anyhow::Result<()> {
+    let quantity = quantity_option(options, "quantity", quest::DEFAULT_QUESTS_NUM as usize);
+    let (filter, draw_options) = match quest::parse_draw_tokens(quest_tokens(options), quantity) {
+        Ok(parsed) => parsed,
+        Err(err) => return respond(ctx, command, err).await,
+    };
+
+    if quest::is_unrated_difficulty(pool, &filter).await? {
+        return respond(ctx, command, quest::UNRATED_DIFFICULTY).await;
+    }
+
+    let candidates = quest::find_candidates(pool, &filter, *command.user.id.as_u64()).await?;
+    let seed = draw_options.seed_or(None);
+    let scenarios = draw::draw(
+        candidates,
+        quantity,
+        &draw_options,
+        &mut draw::seeded_rng(&seed),
+    );
+    if scenarios.is_empty() {
+        return respond(ctx, command, "Could not find any quests.").await;
+    }
+    respond(ctx, command, quest::format_draw(&scenarios, &seed)).await?;
+
+    if let Some(window) = draw_options.vote.filter(|_| scenarios.len() > 1) {
+        let message = command.get_interaction_response(&ctx.http).await?;
+        let winner =
+            vote::collect_votes(ctx, &message, scenarios.len(), Duration::from_secs(window))
+                .await?;
+        follow_up(
+            ctx,
+            command,
+            quest::format_vote(winner.and_then(|index| scenarios.get(index))),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn event_quest(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &ApplicationCommandInteraction,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> anyhow::Result<()> {
+    let quantity = quantity_option(options, "quantity", quest::DEFAULT_QUESTS_NUM as usize);
+    let (filter, draw_options) = match quest::parse_draw_tokens(quest_tokens(options), quantity) {
+        Ok(parsed) => parsed,
+        Err(err) => return respond(ctx, command, err).await,
+    };
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => return respond(ctx, command, "No active event found.").await,
+    };
+
+    if quest::is_unrated_difficulty(pool, &filter).await? {
+        return respond(ctx, command, quest::UNRATED_DIFFICULTY).await;
+    }
+
+    let candidates =
+        quest::find_event_candidates(pool, event.id, &filter, *command.user.id.as_u64()).await?;
+    let seed = draw_options.seed_or(event.seed.as_deref());
+    let scenarios = draw::draw(
+        candidates,
+        quantity,
+        &draw_options,
+        &mut draw::seeded_rng(&seed),
+    );
+    if scenarios.is_empty() {
+        return respond(ctx, command, "No more quests registered with this event.").await;
+    }
+    respond(
+        ctx,
+        command,
+        group_scenarios::format_event_draw(&scenarios, &seed),
+    )
+    .await?;
+
+    if let Some(window) = draw_options.vote.filter(|_| scenarios.len() > 1) {
+        let message = command.get_interaction_response(&ctx.http).await?;
+        let winner =
+            vote::collect_votes(ctx, &message, scenarios.len(), Duration::from_secs(window))
+                .await?;
+        let message = group_scenarios::checkout_vote(
+            ctx,
+            pool,
+            &event,
+            &command.user,
+            winner.and_then(|index| scenarios.get(index)),
+        )
+        .await?;
+        follow_up(ctx, command, message).await?;
+    }
+
+    Ok(())
+}
+
+/// The single match for the query, otherwise the reply saying there's none or several
+fn single_match<'a, T>(
+    query: &str,
+    mut matches: Vec<&'a T>,
+    code: impl Fn(&T) -> &str,
+    title: impl Fn(&T) -> &str,
+) -> Result<&'a T, String> {
+    match matches.len() {
+        0 => Err(format!("Nothing found by: {}", query)),
+        1 => Ok(matches.remove(0)),
+        _ => Err(format!(
+            "Several matched '{}', use one of their codes:\n{}",
+            query,
+            utils::format_matches(&matches, code, title)
+        )),
+    }
+}
+
+/// Find the event scenario given by the `scenario` option. When it's missing or ambiguous, say so
+/// and return `None`.
+async fn find_event_scenario(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &ApplicationCommandInteraction,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> anyhow::Result<Option<(Event, Scenario)>> {
+    let query = string_option(options, "scenario").unwrap_or_default();
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => {
+            respond(ctx, command, "No active event found.").await?;
+
+            return Ok(None);
+        }
+    };
+
+    let scenarios = group_scenarios::match_scenarios(pool, &event, query).await?;
+    match single_match(
+        query,
+        scenarios.iter().collect(),
+        |scenario| &scenario.code,
+        |scenario| &scenario.title,
+    ) {
+        Ok(scenario) => Ok(Some((event, scenario.clone()))),
+        Err(reply) => {
+            respond(ctx, command, reply).await?;
+
+            Ok(None)
+        }
+    }
+}
+
+async fn event_complete(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &ApplicationCommandInteraction,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> anyhow::Result<()> {
+    if let Some((event, scenario)) = find_event_scenario(ctx, pool, command, options).await? {
+        let (message, recorded) =
+            group_scenarios::complete_scenario(ctx, pool, &event, &command.user, &scenario).await?;
+        respond(ctx, command, message).await?;
+        if recorded {
+            milestones::announce(ctx, pool, &event, command.channel_id).await;
+            rewards::grant(
+                ctx,
+                pool,
+                &event,
+                command.guild_id,
+                command.channel_id,
+                &[&command.user],
+            )
+            .await;
+        }
+    }
+
+    Ok(())
+}
+
+async fn event_checkout(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &ApplicationCommandInteraction,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> anyhow::Result<()> {
+    if let Some((event, scenario)) = find_event_scenario(ctx, pool, command, options).await? {
+        let message =
+            group_scenarios::checkout_scenario(ctx, pool, &event, &command.user, &scenario).await?;
+        respond(ctx, command, message).await?;
+    }
+
+    Ok(())
+}
+
+async fn event_progress(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &ApplicationCommandInteraction,
+) -> anyhow::Result<()> {
+    match Event::find_by_active(pool, true).await? {
+        Some(event) => {
+            respond_card(
+                ctx,
+                pool,
+                command,
+                &group_scenarios::progress_card(pool, &event).await?,
+            )
+            .await
+        }
+        None => respond(ctx, command, "No active event found.").await,
+    }
+}
+
+async fn event_conquer(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &ApplicationCommandInteraction,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> anyhow::Result<()> {
+    let query = string_option(options, "challenge").unwrap_or_default();
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => return respond(ctx, command, "No active event found.").await,
+    };
+
+    let challenges = event.find_challenges(pool, Some(KANG_ATTRIBUTE)).await?;
+    let challenge = match single_match(
+        query,
+        utils::find_matches(
+            query,
+            &challenges,
+            |challenge| &challenge.code,
+            |challenge| &challenge.name,
+        ),
+        |challenge| &challenge.code,
+        |challenge| &challenge.name,
+    ) {
+        Ok(challenge) => challenge,
+        Err(reply) => return respond(ctx, command, reply).await,
+    };
+
+    let players = players(&command.user, &command.data, options);
+    let message = event::complete_challenge(pool, challenge, &players).await?;
+    respond(ctx, command, message).await?;
+    milestones::announce(ctx, pool, &event, command.channel_id).await;
+    rewards::grant(
+        ctx,
+        pool,
+        &event,
+        command.guild_id,
+        command.channel_id,
+        &players,
+    )
+    .await;
+
+    Ok(())
+}
+
+async fn gauntlet(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &ApplicationCommandInteraction,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> anyhow::Result<()> {
+    let quantity = quantity_option(options, "modifiers", DEFAULT_MODIFIERS_NUM);
+    if quantity == 0 {
+        return respond(ctx, command, "A gauntlet needs at least one modifier.").await;
+    }
+    let players = players(&command.user, &command.data, options);
+    let gauntlet_args = match GauntletArgs::from_tokens(
+        bool_option(options, "quest"),
+        players
+            .iter()
+            .skip(1)
+            .map(|player| *player.id.as_u64())
+            .collect(),
+        quest_tokens(options),
+    ) {
+        Ok(parsed) => parsed,
+        Err(err) => return respond(ctx, command, err).await,
+    };
+
+    let message = challenge::build_gauntlet(pool, &players, quantity, &gauntlet_args).await?;
+    respond(ctx, command, message).await
+}
+
+/// Suggestions for a partially typed code or title: the codes starting with it, then the titles
+/// matching it
+fn suggest<'a, T>(
+    query: &str,
+    items: &'a [T],
+    code: impl Fn(&T) -> &str,
+    title: impl Fn(&T) -> &str,
+) -> Vec<&'a T> {
+    let query = query.trim();
+    let mut suggestions: Vec<&T> = items
+        .iter()
+        .filter(|item| code(item).to_lowercase().starts_with(&query.to_lowercase()))
+        .collect();
+    if !query.is_empty() {
+        for item in fuzzy::find_matches(query, items, &title) {
+            if !suggestions
+                .iter()
+                .any(|suggestion| std::ptr::eq(*suggestion, item))
+            {
+                suggestions.push(item);
+            }
+        }
+    }
+    suggestions.truncate(MAX_SUGGESTIONS);
+
+    suggestions
+}
+
+/// Format a suggestion as `title (code)`, cutting the title short to fit
+fn suggestion_name(title: &str, code: &str) -> String {
+    let suffix = format!(" ({})", code);
+    let room = MAX_SUGGESTION_LENGTH.saturating_sub(suffix.chars().count());
+    let title: String = if title.chars().count() > room {
+        title
+            .chars()
+            .take(room.saturating_sub(1))
+            .chain(std::iter::once('…'))
+            .collect()
+    } else {
+        title.to_string()
+    };
+
+    format!("{}{}", title, suffix)
+}
+
+/// Suggest the active event's open scenarios and challenges as the user types them
+pub async fn autocomplete(
+    ctx: &Context,
+    interaction: &AutocompleteInteraction,
+) -> anyhow::Result<()> {
+    let data = ctx.data.read().await;
+    let pool = data
+        .get::<PostgresPool>()
+        .expect("Expected PostgresPool in TypeMap.");
+
+    let focused = match interaction
+        .data
+        .options
+        .iter()
+        .flat_map(|option| std::iter::once(option).chain(option.options.iter()))
+        .find(|option| option.focused)
+    {
+        Some(focused) => focused,
+        None => return Ok(()),
+    };
+    let query = focused
+        .value
+        .as_ref()
+        .and_then(|value| value.as_str())
+        .unwrap_or_default();
+    let event = match Event::find_by_active(pool, true).await? {
+        Some(event) => event,
+        None => return Ok(()),
+    };
+
+    // suggestions are (name, code) pairs
+    let suggestions: Vec<(String, String)> = match focused.name.as_str() {
+        "scenario" => {
+            let scenarios = event.find_open_scenarios(pool).await?;
+            let mut suggestions = suggest(
+                query,
+                &scenarios,
+                |scenario| &scenario.code,
+                |scenario| &scenario.title,
+            );
+            // an alias is only suggested when the scenario is still open
+            if let Some(alias) = Scenario::find_by_alias(pool, query).await? {
+                if let Some(scenario) = scenarios.iter().find(|scenario| scenario.id == alias.id) {
+                    suggestions.retain(|suggestion| suggestion.id != scenario.id);
+                    suggestions.insert(0, scenario);
+                    suggestions.truncate(MAX_SUGGESTIONS);
+                }
+            }
+
+            suggestions
+                .into_iter()
+                .map(|scenario| {
+                    (
+                        suggestion_name(&scenario.title, &scenario.code),
+                        scenario.code.clone(),
+                    )
+                })
+                .collect()
+        }
+        "challenge" => {
+            let challenges = event
+                .find_incomplete_challenges(
+                    pool,
+                    *interaction.user.id.as_u64(),
+                    Some(KANG_ATTRIBUTE),
+                )
+                .await?;
+
+            suggest(
+                query,
+                &challenges,
+                |challenge| &challenge.code,
+                |challenge| &challenge.name,
+            )
+            .into_iter()
+            .map(|challenge| {
+                (
+                    suggestion_name(&challenge.name, &challenge.code),
+                    challenge.code.clone(),
+                )
+            })
+            .collect()
+        }
+        _ => return Ok(()),
+    };
+
+    interaction
+        .create_autocomplete_response(&ctx.http, |response| {
+            for (name, code) in suggestions {
+                response.add_string_choice(name, code);
+            }
+
+            response
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_turns_options_into_tokens() {
+        let options: Vec<ApplicationCommandInteractionDataOption> =
+            serde_json::from_value(serde_json::json!([
+                { "name": "quantity", "type": 4, "value": 5 },
+                { "name": "cycle", "type": 3, "value": "Angmar Awakened" },
+                { "name": "unbeaten", "type": 5, "value": true },
+                { "name": "weighted", "type": 5, "value": false },
+                { "name": "vote", "type": 4, "value": 120 },
+            ]))
+            .unwrap();
+
+        assert_eq!(
+            quest_tokens(&options),
+            vec!["cycle:Angmar Awakened", "unbeaten", "vote:120"]
+        );
+        assert_eq!(quantity_option(&options, "quantity", 3), 5);
+        assert_eq!(quantity_option(&options, "modifiers", 1), 1);
+    }
+
+    #[test]
+    fn it_suggests_codes_then_titles() {
+        let scenarios = [
+            ("0101", "Passage Through Mirkwood"),
+            ("0102", "Journey Along the Anduin"),
+            ("0201", "The Hunt for Gollum"),
+            ("1001", "Return to Mirkwood"),
+        ];
+        let suggestions = |query| {
+            suggest(query, &scenarios, |(code, _)| code, |(_, title)| title)
+                .into_iter()
+                .map(|(code, _)| *code)
+                .collect::<Vec<&str>>()
+        };
+
+        assert_eq!(suggestions(""), vec!["0101", "0102", "0201", "1001"]);
+        assert_eq!(suggestions("01"), vec!["0101", "0102"]);
+        assert_eq!(suggestions("mirkwood"), vec!["0101", "1001"]);
+        assert_eq!(suggestions("10"), vec!["1001"]);
+    }
+
+    #[test]
+    fn it_fits_suggestion_names() {
+        assert_eq!(
+            suggestion_name("The Hunt for Gollum", "0201"),
+            "The Hunt for Gollum (0201)"
+        );
+
+        let name = suggestion_name(&"a".repeat(150), "0201");
+        assert_eq!(name.chars().count(), MAX_SUGGESTION_LENGTH);
+        assert!(name.ends_with("… (0201)"));
+    }
+}
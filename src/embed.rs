@@ -0,0 +1,258 @@
+//! Replies rendered as embeds, or as plain text on servers that have turned embeds off
+use crate::{
+    models::{event::Event, guild::GuildSettings},
+    utils,
+};
+use serenity::{
+    builder::CreateEmbed,
+    model::{channel::Message, id::GuildId},
+    prelude::Context,
+};
+use sqlx::postgres::PgPool;
+
+/// Discord cuts embed descriptions off at 4096 characters
+const DESCRIPTION_LIMIT: usize = 4096;
+/// Discord allows at most 25 fields in an embed
+const FIELDS_LIMIT: usize = 25;
+/// Discord cuts field values off at 1024 characters
+const FIELD_VALUE_LIMIT: usize = 1024;
+/// Discord rejects embeds with more than 6000 characters in total, leave some room for the title
+const EMBED_LIMIT: usize = 5500;
+/// Number of segments in a progress bar
+const PROGRESS_BAR_LENGTH: usize = 20;
+
+/// A reply with a title, lines of text and fields, styled after the event it's about
+#[derive(Debug, Default)]
+pub struct Card {
+    pub title: String,
+    pub lines: Vec<String>,
+    /// Named sections, i.e. a scenario and its challenges
+    pub fields: Vec<(String, String)>,
+    pub footer: Option<String>,
+    pub color: Option<i32>,
+    pub thumbnail_url: Option<String>,
+}
+
+/// A bar showing how far along something is, i.e. `▰▰▰▰▱▱▱▱ 50.00%`
+pub fn progress_bar(percent: f64) -> String {
+    let percent = percent.clamp(0.0, 100.0);
+    let filled = ((percent / 100.0) * PROGRESS_BAR_LENGTH as f64).round() as usize;
+
+    format!(
+        "{}{} {:.2}%",
+        "▰".repeat(filled),
+        "▱".repeat(PROGRESS_BAR_LENGTH - filled),
+        percent
+    )
+}
+
+/// Cut text short enough to fit the limit
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() > limit {
+        text.chars()
+            .take(limit - 1)
+            .chain(std::iter::once('…'))
+            .collect()
+    } else {
+        text.to_string()
+    }
+}
+
+impl Card {
+    pub fn new(title: impl ToString) -> Card {
+        Card {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// A card in the event's color, with its thumbnail
+    pub fn for_event(title: impl ToString, event: &Event) -> Card {
+        Card {
+            color: event.color,
+            thumbnail_url: event.thumbnail_url.clone(),
+            ..Card::new(title)
+        }
+    }
+
+    /// The card as plain text, in as few messages as fit
+    pub fn to_plain_text(&self) -> Vec<String> {
+        let mut items = vec![format!("**{}**", self.title)];
+        items.extend(self.lines.iter().cloned());
+        items.extend(
+            self.fields
+                .iter()
+                .map(|(name, value)| format!("**{}**\n{}", name, value)),
+        );
+        items.extend(self.footer.iter().cloned());
+
+        utils::paginate(&items)
+    }
+
+    /// The card as embeds. It only takes one unless the lines or fields don't fit, in which case
+    /// the rest carry on in more embeds of the same color. A field too long for Discord carries on
+    /// in more fields of the same name.
+    pub fn to_embeds(&self) -> Vec<CreateEmbed> {
+        let descriptions = utils::pack(&self.lines, DESCRIPTION_LIMIT);
+        // the fields share the last embed with its description
+        let mut length = descriptions
+            .last()
+            .map_or(0, |description| description.chars().count());
+        let mut embeds: Vec<CreateEmbed> = descriptions
+            .into_iter()
+            .map(|description| {
+                let mut embed = CreateEmbed::default();
+                embed.description(description);
+                embed
+            })
+            .collect();
+
+        let mut fields = 0;
+        for (name, value) in self.fields.iter() {
+            let name = truncate(name, 256);
+            for value in utils::pack(&[value], FIELD_VALUE_LIMIT) {
+                let field_length = name.chars().count() + value.chars().count();
+                if embeds.is_empty()
+                    || fields == FIELDS_LIMIT
+                    || length + field_length > EMBED_LIMIT
+                {
+                    embeds.push(CreateEmbed::default());
+                    length = 0;
+                    fields = 0;
+                }
+                if let Some(embed) = embeds.last_mut() {
+                    embed.field(&name, value, false);
+                }
+                length += field_length;
+                fields += 1;
+            }
+        }
+        if embeds.is_empty() {
+            embeds.push(CreateEmbed::default());
+        }
+
+        let last = embeds.len() - 1;
+        for (i, embed) in embeds.iter_mut().enumerate() {
+            if let Some(color) = self.color {
+                embed.color(color as u32);
+            }
+            if i == 0 {
+                embed.title(truncate(&self.title, 256));
+                if let Some(thumbnail_url) = &self.thumbnail_url {
+                    embed.thumbnail(thumbnail_url);
+                }
+            }
+            if i == last {
+                if let Some(footer) = &self.footer {
+                    embed.footer(|f| f.text(footer));
+                }
+            }
+        }
+
+        embeds
+    }
+}
+
+/// Whether the server has turned embeds off
+pub async fn guild_plain_text(pool: &PgPool, guild_id: Option<GuildId>) -> anyhow::Result<bool> {
+    Ok(match guild_id {
+        Some(guild_id) => GuildSettings::find(pool, guild_id.0).await?.plain_text,
+        None => false,
+    })
+}
+
+/// Whether the server the message came from has turned embeds off
+pub async fn plain_text(pool: &PgPool, msg: &Message) -> anyhow::Result<bool> {
+    guild_plain_text(pool, msg.guild_id).await
+}
+
+/// Send the card to the channel the message came from, as plain text when the server has turned
+/// embeds off. Returns the last message sent.
+pub async fn send_card(
+    ctx: &Context,
+    pool: &PgPool,
+    msg: &Message,
+    card: &Card,
+) -> anyhow::Result<Option<Message>> {
+    let mut message = None;
+    if plain_text(pool, msg).await? {
+        for page in card.to_plain_text() {
+            message = Some(msg.channel_id.say(&ctx.http, page).await?);
+        }
+    } else {
+        for embed in card.to_embeds() {
+            message = Some(
+                msg.channel_id
+                    .send_message(&ctx.http, |m| m.set_embed(embed))
+                    .await?,
+            );
+        }
+    }
+
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_draws_progress_bars() {
+        assert_eq!(progress_bar(0.0), format!("{} 0.00%", "▱".repeat(20)));
+        assert_eq!(
+            progress_bar(42.5),
+            format!("{}{} 42.50%", "▰".repeat(9), "▱".repeat(11))
+        );
+        assert_eq!(progress_bar(120.0), format!("{} 100.00%", "▰".repeat(20)));
+    }
+
+    #[test]
+    fn it_renders_plain_text() {
+        let card = Card {
+            lines: vec!["Line".to_string()],
+            fields: vec![("Scenario".to_string(), "- Challenge".to_string())],
+            footer: Some("Seed: `abc`".to_string()),
+            ..Card::new("Title")
+        };
+
+        assert_eq!(
+            card.to_plain_text(),
+            vec!["**Title**\nLine\n**Scenario**\n- Challenge\nSeed: `abc`"]
+        );
+    }
+
+    #[test]
+    fn it_carries_fields_over_into_more_embeds() {
+        let card = Card {
+            fields: (0..30)
+                .map(|i| (format!("Scenario {}", i), "- Challenge".to_string()))
+                .collect(),
+            ..Card::new("Title")
+        };
+
+        assert_eq!(card.to_embeds().len(), 2);
+        assert_eq!(Card::new("Title").to_embeds().len(), 1);
+    }
+
+    #[test]
+    fn it_splits_long_fields_instead_of_cutting_them_off() {
+        let challenges: Vec<String> = (0..20)
+            .map(|i| format!("- Challenge {}: {}", i, "x".repeat(100)))
+            .collect();
+        let card = Card {
+            fields: vec![("Scenario".to_string(), challenges.join("\n"))],
+            ..Card::new("Title")
+        };
+
+        let embeds = card.to_embeds();
+        assert_eq!(embeds.len(), 1);
+        let fields = embeds[0].0["fields"].as_array().unwrap();
+        assert_eq!(fields.len(), 3);
+        assert!(fields
+            .iter()
+            .all(|field| field["value"].as_str().unwrap().chars().count() <= FIELD_VALUE_LIMIT));
+        assert!(!fields
+            .iter()
+            .any(|field| field["value"].as_str().unwrap().contains('…')));
+    }
+}
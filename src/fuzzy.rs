@@ -0,0 +1,129 @@
+//! Fuzzy matching of titles typed in chat, i.e. "khazad dum" or "mim" for "Khazad-dûm" or "Mîm"
+use strsim::{jaro_winkler, normalized_damerau_levenshtein};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Minimum similarity for a whole title to match
+const TITLE_SIMILARITY: f64 = 0.85;
+/// Minimum similarity for a word of the query to match a word of the title
+const WORD_SIMILARITY: f64 = 0.75;
+
+/// Fold diacritics and case, and treat punctuation as whitespace
+pub fn normalize(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Every word of the query is close to a word in the title, or the start of one
+fn words_match(query: &str, title: &str) -> bool {
+    query.split_whitespace().all(|query_word| {
+        title.split_whitespace().any(|title_word| {
+            title_word.starts_with(query_word)
+                || normalized_damerau_levenshtein(query_word, title_word) >= WORD_SIMILARITY
+        })
+    })
+}
+
+/// Find the items whose title matches the query, best matches first. An exact match wins outright,
+/// then titles containing the query, and only then fuzzy matches.
+pub fn find_matches<'a, T>(query: &str, items: &'a [T], title: impl Fn(&T) -> &str) -> Vec<&'a T> {
+    let query = normalize(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let titles: Vec<(&T, String)> = items
+        .iter()
+        .map(|item| (item, normalize(title(item))))
+        .collect();
+
+    let exact: Vec<&T> = titles
+        .iter()
+        .filter(|(_, title)| *title == query)
+        .map(|(item, _)| *item)
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    let containing: Vec<&T> = titles
+        .iter()
+        .filter(|(_, title)| title.contains(&query))
+        .map(|(item, _)| *item)
+        .collect();
+    if !containing.is_empty() {
+        return containing;
+    }
+
+    let mut fuzzy: Vec<(&T, f64)> = titles
+        .iter()
+        .filter_map(|(item, title)| {
+            let similarity = jaro_winkler(&query, title);
+            if similarity >= TITLE_SIMILARITY || words_match(&query, title) {
+                Some((*item, similarity))
+            } else {
+                None
+            }
+        })
+        .collect();
+    fuzzy.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+    fuzzy.into_iter().map(|(item, _)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TITLES: [&str; 5] = [
+        "Passage Through Mirkwood",
+        "Journey Along the Anduin",
+        "Into the Pit",
+        "The Seventh Level",
+        "Flight from Moria",
+    ];
+
+    fn matches(query: &str, titles: &[&'static str]) -> Vec<&'static str> {
+        find_matches(query, titles, |title| title)
+            .into_iter()
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn it_normalizes_diacritics_and_punctuation() {
+        assert_eq!(normalize("Khazad-dûm"), "khazad dum");
+        assert_eq!(normalize("  Mîm's  Hall "), "mim s hall");
+    }
+
+    #[test]
+    fn it_prefers_exact_then_partial_matches() {
+        assert_eq!(
+            matches("the pit", &["The Pit", "Into the Pit"]),
+            vec!["The Pit"]
+        );
+        assert_eq!(
+            matches("mirkwood", &TITLES),
+            vec!["Passage Through Mirkwood"]
+        );
+        assert_eq!(
+            matches("khazad dum", &["Khazad-dûm", "Dwarrowdelf"]),
+            vec!["Khazad-dûm"]
+        );
+    }
+
+    #[test]
+    fn it_matches_typos() {
+        assert_eq!(
+            matches("jurney anduin", &TITLES),
+            vec!["Journey Along the Anduin"]
+        );
+        assert_eq!(matches("seventh levle", &TITLES), vec!["The Seventh Level"]);
+        assert!(matches("return to mirkwood", &["Dol Guldur"]).is_empty());
+        assert!(matches("", &TITLES).is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::set::ProductType;
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -26,6 +27,8 @@ pub struct Scenario {
     pub title: String,
     pub set: String,
     pub number: i16,
+    pub product_type: ProductType,
+    pub cycle: Option<String>,
 }
 
 pub async fn fetch() -> Result<Vec<Scenario>, Box<dyn std::error::Error>> {
@@ -44,17 +47,15 @@ pub async fn fetch() -> Result<Vec<Scenario>, Box<dyn std::error::Error>> {
     deluxes.insert("The Wilds of Rhovanion", "Ered Mithrin");
     deluxes.insert("A Shadow in the East", "Vengeance of Mordor");
     deluxes.insert("Children of Eorl", "Oaths of the Rohirrim");
-    let others = [
-        "The Hobbit: Over Hill and Under Hill",
-        "The Hobbit: On the Doorstep",
-        "The Black Riders",
-        "The Road Darkens",
-        "The Treason of Saruman",
-        "The Land of Shadow",
-        "The Flame of the West",
-        "The Mountain of Fire",
-        "Two-Player Limited Edition Starter",
-    ];
+    let mut sagas = HashMap::new();
+    sagas.insert("The Hobbit: Over Hill and Under Hill", "The Hobbit");
+    sagas.insert("The Hobbit: On the Doorstep", "The Hobbit");
+    sagas.insert("The Black Riders", "The Lord of the Rings");
+    sagas.insert("The Road Darkens", "The Lord of the Rings");
+    sagas.insert("The Treason of Saruman", "The Lord of the Rings");
+    sagas.insert("The Land of Shadow", "The Lord of the Rings");
+    sagas.insert("The Flame of the West", "The Lord of the Rings");
+    sagas.insert("The Mountain of Fire", "The Lord of the Rings");
 
     let mut last_deluxe = String::new();
     // Hall of Beorn has duplicate numbers in the data, so we'll increment ourselves
@@ -68,18 +69,37 @@ pub async fn fetch() -> Result<Vec<Scenario>, Box<dyn std::error::Error>> {
                 last_deluxe = s.product.clone();
             }
 
-            let set = if s.product.parse::<u32>().is_ok()
+            let (set, product_type, cycle) = if s.product.parse::<u32>().is_ok()
                 || s.product == "The Hunt for the Dreadnaught"
                 || s.product == "The Scouring of the Shire"
             {
                 standalone_scenario_number += 1;
-                "Standalone Scenarios".to_string()
-            } else if others.iter().find(|&&o| o == s.product).is_some() {
-                s.product.clone()
+                (
+                    "Standalone Scenarios".to_string(),
+                    ProductType::Standalone,
+                    None,
+                )
+            } else if s.product == "Two-Player Limited Edition Starter" {
+                (s.product.clone(), ProductType::Starter, None)
+            } else if let Some(cycle) = sagas.get(s.product.as_str()) {
+                (
+                    s.product.clone(),
+                    ProductType::Saga,
+                    Some(cycle.to_string()),
+                )
             } else if s.product != last_deluxe {
-                deluxes.get(last_deluxe.as_str()).unwrap().to_string()
+                let cycle = deluxes.get(last_deluxe.as_str()).unwrap().to_string();
+
+                (cycle.clone(), ProductType::AdventurePack, Some(cycle))
             } else {
-                last_deluxe.clone()
+                let product_type = if last_deluxe == "Core Set" {
+                    ProductType::Core
+                } else {
+                    ProductType::Deluxe
+                };
+                let cycle = deluxes.get(last_deluxe.as_str()).unwrap().to_string();
+
+                (last_deluxe.clone(), product_type, Some(cycle))
             };
 
             let number = if set == "Standalone Scenarios" {
@@ -100,6 +120,8 @@ pub async fn fetch() -> Result<Vec<Scenario>, Box<dyn std::error::Error>> {
                 title: s.title,
                 set,
                 number,
+                product_type,
+                cycle,
             }
         })
         .collect();
@@ -1,2 +1,3 @@
+pub mod fuzzy;
 pub mod hob_scenario_parser;
 pub mod models;
@@ -1,11 +1,21 @@
 mod commands;
+mod embed;
+mod fuzzy;
 mod models;
 mod utils;
 
 use commands::{
+    campaign::*,
+    catalog::*,
     challenge::*,
+    collection::*,
+    completionist::*,
+    constraint::*,
     event::{admin::*, group_scenarios::*, kang::*},
+    play::*,
+    profile::*,
     quest::*,
+    score::*,
 };
 use serenity::{
     async_trait,
@@ -17,6 +27,8 @@ use serenity::{
     },
     model::{
         gateway::Ready,
+        id::{ChannelId, GuildId, MessageId},
+        interactions::Interaction,
         prelude::{Message, UserId},
     },
     prelude::{Context, EventHandler},
@@ -28,13 +40,68 @@ use tracing::{error, info, instrument};
 struct Handler;
 #[async_trait]
 impl EventHandler for Handler {
-    async fn ready(&self, _: Context, ready: Ready) {
+    async fn ready(&self, ctx: Context, ready: Ready) {
         info!("{} is connected!", ready.user.name);
+
+        if let Err(why) = commands::slash::register(&ctx).await {
+            error!("Unable to register slash commands: {:?}", why);
+        }
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) {
+        let data = ctx.data.read().await;
+        let pool = data
+            .get::<utils::PostgresPool>()
+            .expect("Expected PostgresPool in TypeMap.");
+
+        if let Err(why) =
+            commands::event::status::restore(&ctx, pool, channel_id, deleted_message_id).await
+        {
+            error!("Unable to restore the event status: {:?}", why);
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::ApplicationCommand(command) => {
+                info!(
+                    command = command.data.name.as_str(),
+                    user = command.user.name.as_str()
+                );
+
+                if let Err(why) = commands::slash::handle(&ctx, &command).await {
+                    error!("Error running slash command: {:?}", why);
+                }
+            }
+            Interaction::Autocomplete(autocomplete) => {
+                if let Err(why) = commands::slash::autocomplete(&ctx, &autocomplete).await {
+                    error!("Error suggesting slash command options: {:?}", why);
+                }
+            }
+            _ => {}
+        }
     }
 }
 
 #[group]
-#[commands(gauntlet, quest)]
+#[commands(
+    completionist,
+    constraints,
+    log,
+    played,
+    profile,
+    quest,
+    scenario_info,
+    score,
+    set_info,
+    sets
+)]
 struct General;
 
 #[group]
@@ -42,13 +109,15 @@ struct General;
 #[description = "Set of Commands for interacting with an event."]
 #[sub_groups("EventAdmin", "EventKang")]
 //#[commands(ccomplete, cgroup, cgroupall, cgroupprogress, cprogress)]
-#[commands(equest, complete, checkout, progress)]
+#[commands(equest, complete, checkout, progress, best)]
 struct Event;
 
 #[group]
 #[prefix = "admin"]
 #[allowed_roles("Tech Team")]
-#[commands(add, archive, cload, create, set)]
+#[commands(
+    add, alias, archive, cload, create, embeds, milestones, reward, seed, set, status, style
+)]
 struct EventAdmin;
 
 #[group]
@@ -57,6 +126,27 @@ struct EventAdmin;
 #[commands(conquer, kall, kprogress, mission)]
 struct EventKang;
 
+#[group]
+#[prefix = "collection"]
+#[description = "Set of Commands for tracking the sets you own."]
+#[default_command(owned)]
+#[commands(own, disown, owned)]
+struct Collection;
+
+#[group]
+#[prefixes("gauntlet", "pain")]
+#[description = "Set of Commands for building and tracking gauntlets."]
+#[default_command(gauntlet)]
+#[commands(result, fame)]
+struct Gauntlet;
+
+#[group]
+#[prefix = "campaign"]
+#[description = "Set of Commands for keeping a saga campaign log."]
+#[default_command(show)]
+#[commands(start, show, record)]
+struct Campaign;
+
 #[help]
 #[individual_command_tip = "If you want more information about a specific command, just pass the command as argument."]
 #[lacking_role("hide")]
@@ -91,6 +181,10 @@ async fn main() {
 
     let discord_token =
         std::env::var("DISCORD_TOKEN").expect("Please provide the env var DISCORD_TOKEN");
+    let application_id = std::env::var("APPLICATION_ID")
+        .expect("Please provide the env var APPLICATION_ID")
+        .parse::<u64>()
+        .expect("The env var APPLICATION_ID must be a number");
     let database_url =
         std::env::var("DATABASE_URL").expect("Please provide the env var DATABASE_URL");
 
@@ -104,6 +198,7 @@ async fn main() {
         });
 
     let mut client = Client::builder(discord_token)
+        .application_id(application_id)
         .event_handler(Handler)
         .framework(
             StandardFramework::new()
@@ -113,7 +208,10 @@ async fn main() {
                 .group(&EVENT_GROUP)
                 .group(&GENERAL_GROUP)
                 .group(&EVENTADMIN_GROUP)
-                .group(&EVENTKANG_GROUP),
+                .group(&EVENTKANG_GROUP)
+                .group(&COLLECTION_GROUP)
+                .group(&GAUNTLET_GROUP)
+                .group(&CAMPAIGN_GROUP),
         )
         .await
         .unwrap_or_else(|_| {
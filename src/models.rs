@@ -1,5 +1,13 @@
+pub mod achievement;
+pub mod campaign;
 pub mod challenge;
 pub mod event;
+pub mod gauntlet;
+pub mod guild;
+pub mod milestone;
+pub mod play;
+pub mod reward;
 pub mod scenario;
+pub mod score;
 pub mod set;
 pub mod user;
@@ -0,0 +1,212 @@
+use crate::models::play::Difficulty;
+use sqlx::postgres::PgPool;
+use std::fmt;
+
+/// The kind of activity an achievement counts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AchievementSource {
+    /// A game logged with `!log` or `!played`
+    Play,
+    /// A gauntlet's result
+    Gauntlet,
+    /// An event challenge completed
+    Challenge,
+    /// An event quest completed
+    Quest,
+}
+
+impl AchievementSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AchievementSource::Play => "play",
+            AchievementSource::Gauntlet => "gauntlet",
+            AchievementSource::Challenge => "challenge",
+            AchievementSource::Quest => "quest",
+        }
+    }
+}
+
+impl fmt::Display for AchievementSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Something a player just did, checked against the achievements that count it
+pub struct Activity {
+    pub source: AchievementSource,
+    pub scenario_id: Option<i64>,
+    pub victory: Option<bool>,
+    pub difficulty: Option<Difficulty>,
+}
+
+pub struct Achievement {
+    pub name: String,
+    pub description: String,
+}
+
+/// An achievement and how far a player has got towards it
+pub struct AchievementProgress {
+    pub name: String,
+    pub description: String,
+    pub progress: i32,
+    pub target: i64,
+    pub earned: bool,
+}
+
+impl Achievement {
+    /// Count the activity towards each of the player's achievements it matches, returning the
+    /// ones it earned. Only the activity itself is looked at, since the progress so far is kept
+    /// as it's made.
+    pub async fn record(
+        pool: &PgPool,
+        user_id: i64,
+        activity: &Activity,
+    ) -> anyhow::Result<Vec<Achievement>> {
+        let candidates = sqlx::query!(
+            r#"
+SELECT achievements.id, achievements.every_scenario
+FROM achievements
+WHERE achievements.source = $1
+    AND (achievements.victory IS NULL OR achievements.victory = $2)
+    AND (achievements.difficulty IS NULL OR achievements.difficulty = $3)
+    AND (
+        (achievements.cycle IS NULL AND achievements.set_name IS NULL)
+        OR EXISTS (
+            SELECT 1
+            FROM scenarios, sets
+            WHERE scenarios.id = $4
+                AND sets.id = scenarios.set_id
+                AND (achievements.cycle IS NULL OR LOWER(sets.cycle) = LOWER(achievements.cycle))
+                AND (achievements.set_name IS NULL OR LOWER(sets.name) = LOWER(achievements.set_name))
+        )
+    )
+    AND NOT EXISTS (
+        SELECT 1
+        FROM user_achievements
+        WHERE user_achievements.achievement_id = achievements.id
+            AND user_achievements.user_id = $5
+            AND user_achievements.earned_at IS NOT NULL
+    )
+ORDER BY achievements.id
+"#,
+            activity.source.as_str(),
+            activity.victory,
+            activity.difficulty.map(|difficulty| difficulty.as_str()),
+            activity.scenario_id,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut earned = Vec::new();
+        for candidate in candidates {
+            let mut tx = pool.begin().await?;
+            if candidate.every_scenario {
+                // each scenario only counts once
+                let scenario_id = match activity.scenario_id {
+                    Some(scenario_id) => scenario_id,
+                    None => continue,
+                };
+                let row_count = sqlx::query!(
+                    r#"
+INSERT INTO user_achievement_scenarios ( user_id, achievement_id, scenario_id )
+VALUES ( $1, $2, $3 )
+ON CONFLICT DO NOTHING
+"#,
+                    user_id,
+                    candidate.id,
+                    scenario_id
+                )
+                .execute(&mut tx)
+                .await?
+                .rows_affected();
+                if row_count == 0 {
+                    continue;
+                }
+            }
+
+            sqlx::query!(
+                r#"
+INSERT INTO user_achievements ( user_id, achievement_id, progress )
+VALUES ( $1, $2, 1 )
+ON CONFLICT (user_id, achievement_id)
+DO
+    UPDATE SET progress = user_achievements.progress + 1,
+        updated_at = CURRENT_TIMESTAMP
+"#,
+                user_id,
+                candidate.id
+            )
+            .execute(&mut tx)
+            .await?;
+
+            // only one update gets to earn it, however many happen at once
+            let achievement = sqlx::query_as!(
+                Achievement,
+                r#"
+UPDATE user_achievements
+SET earned_at = CURRENT_TIMESTAMP,
+    updated_at = CURRENT_TIMESTAMP
+FROM achievements
+WHERE user_achievements.user_id = $1
+    AND user_achievements.achievement_id = $2
+    AND achievements.id = user_achievements.achievement_id
+    AND user_achievements.earned_at IS NULL
+    AND user_achievements.progress >= CASE
+        WHEN achievements.every_scenario THEN (
+            SELECT COUNT(scenarios.id)
+            FROM scenarios, sets
+            WHERE sets.id = scenarios.set_id
+                AND (achievements.cycle IS NULL OR LOWER(sets.cycle) = LOWER(achievements.cycle))
+                AND (achievements.set_name IS NULL OR LOWER(sets.name) = LOWER(achievements.set_name))
+        )
+        ELSE achievements.count
+    END
+RETURNING achievements.name, achievements.description
+"#,
+                user_id,
+                candidate.id
+            )
+            .fetch_optional(&mut tx)
+            .await?;
+            tx.commit().await?;
+            earned.extend(achievement);
+        }
+
+        Ok(earned)
+    }
+
+    /// Every achievement along with how far the player has got towards it
+    pub async fn find_progress_by_user(
+        pool: &PgPool,
+        user_id: i64,
+    ) -> anyhow::Result<Vec<AchievementProgress>> {
+        Ok(sqlx::query_as!(
+            AchievementProgress,
+            r#"
+SELECT achievements.name,
+    achievements.description,
+    COALESCE(user_achievements.progress, 0) AS "progress!",
+    CASE
+        WHEN achievements.every_scenario THEN (
+            SELECT COUNT(scenarios.id)
+            FROM scenarios, sets
+            WHERE sets.id = scenarios.set_id
+                AND (achievements.cycle IS NULL OR LOWER(sets.cycle) = LOWER(achievements.cycle))
+                AND (achievements.set_name IS NULL OR LOWER(sets.name) = LOWER(achievements.set_name))
+        )
+        ELSE achievements.count
+    END AS "target!",
+    user_achievements.earned_at IS NOT NULL AS "earned!"
+FROM achievements
+LEFT JOIN user_achievements ON user_achievements.achievement_id = achievements.id
+    AND user_achievements.user_id = $1
+ORDER BY user_achievements.earned_at NULLS LAST, achievements.id
+"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+}
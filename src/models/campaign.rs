@@ -0,0 +1,305 @@
+use crate::models::scenario::Scenario;
+use sqlx::postgres::PgPool;
+use std::fmt;
+
+/// The saga played when a campaign is started without one
+pub const DEFAULT_CAMPAIGN_CYCLE: &str = "The Lord of the Rings";
+
+/// What a campaign log entry records
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogKind {
+    Boon,
+    Burden,
+    FallenHero,
+}
+
+impl LogKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogKind::Boon => "boon",
+            LogKind::Burden => "burden",
+            LogKind::FallenHero => "fallen_hero",
+        }
+    }
+}
+
+impl fmt::Display for LogKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A saga campaign played by a user
+pub struct Campaign {
+    pub id: i64,
+    pub cycle: String,
+    pub threat_penalty: i32,
+}
+
+/// A scenario played as part of a campaign
+pub struct CampaignScenario {
+    pub scenario_id: i64,
+    pub title: String,
+    pub victory: bool,
+    pub threat_penalty: i32,
+}
+
+/// A boon, burden or fallen hero in the campaign log
+pub struct CampaignLogEntry {
+    pub kind: String,
+    pub name: String,
+}
+
+/// The result of a campaign scenario and everything it adds to the log
+#[derive(Debug, Default, PartialEq)]
+pub struct CampaignResult {
+    pub victory: bool,
+    pub threat_penalty: i32,
+    pub boons: Vec<String>,
+    pub burdens: Vec<String>,
+    pub fallen_heroes: Vec<String>,
+}
+
+impl CampaignResult {
+    fn log_entries(&self) -> impl Iterator<Item = (LogKind, &String)> {
+        self.boons
+            .iter()
+            .map(|name| (LogKind::Boon, name))
+            .chain(self.burdens.iter().map(|name| (LogKind::Burden, name)))
+            .chain(
+                self.fallen_heroes
+                    .iter()
+                    .map(|name| (LogKind::FallenHero, name)),
+            )
+    }
+}
+
+/// The next scenario to play: the first one in order that hasn't been won yet
+pub fn next_scenario<'a>(
+    scenarios: &'a [Scenario],
+    played: &[CampaignScenario],
+) -> Option<&'a Scenario> {
+    scenarios.iter().find(|scenario| {
+        !played
+            .iter()
+            .any(|entry| entry.victory && entry.scenario_id == scenario.id)
+    })
+}
+
+impl Campaign {
+    /// Find the saga cycle matching the name, ignoring case
+    pub async fn find_cycle(pool: &PgPool, cycle: &str) -> anyhow::Result<Option<String>> {
+        Ok(sqlx::query!(
+            r#"
+SELECT DISTINCT cycle AS "cycle!"
+FROM sets
+WHERE product_type = 'saga'
+    AND LOWER(cycle) = LOWER($1)
+"#,
+            cycle
+        )
+        .fetch_optional(pool)
+        .await?
+        .map(|row| row.cycle))
+    }
+
+    /// Start a new campaign, replacing the user's active one
+    pub async fn create(pool: &PgPool, user_id: i64, cycle: &str) -> anyhow::Result<Campaign> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+UPDATE campaigns
+SET active = false,
+    updated_at = CURRENT_TIMESTAMP
+WHERE user_id = $1
+    AND active = true
+"#,
+            user_id
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let campaign = sqlx::query_as!(
+            Campaign,
+            r#"
+INSERT INTO campaigns ( user_id, cycle )
+VALUES ( $1, $2 )
+RETURNING id, cycle, threat_penalty
+"#,
+            user_id,
+            cycle
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(campaign)
+    }
+
+    pub async fn find_active_by_user(
+        pool: &PgPool,
+        user_id: i64,
+    ) -> anyhow::Result<Option<Campaign>> {
+        Ok(sqlx::query_as!(
+            Campaign,
+            r#"
+SELECT id, cycle, threat_penalty
+FROM campaigns
+WHERE user_id = $1
+    AND active = true
+"#,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// The campaign's scenarios from the catalog, in set order
+    pub async fn find_scenarios(&self, pool: &PgPool) -> anyhow::Result<Vec<Scenario>> {
+        Ok(sqlx::query_as!(
+            Scenario,
+            r#"
+SELECT scenarios.id, scenarios.title, scenarios.code, scenarios.set_id, scenarios.number, scenarios.difficulty
+FROM scenarios, sets
+WHERE scenarios.set_id = sets.id
+    AND sets.product_type = 'saga'
+    AND sets.cycle = $1
+ORDER BY sets.id, scenarios.number, scenarios.code
+"#,
+            self.cycle
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Every scenario played in the campaign, in the order they were played
+    pub async fn find_played(&self, pool: &PgPool) -> anyhow::Result<Vec<CampaignScenario>> {
+        Ok(sqlx::query_as!(
+            CampaignScenario,
+            r#"
+SELECT campaign_scenarios.scenario_id, scenarios.title, campaign_scenarios.victory, campaign_scenarios.threat_penalty
+FROM campaign_scenarios, scenarios
+WHERE campaign_scenarios.scenario_id = scenarios.id
+    AND campaign_scenarios.campaign_id = $1
+ORDER BY campaign_scenarios.id
+"#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn find_log(&self, pool: &PgPool) -> anyhow::Result<Vec<CampaignLogEntry>> {
+        Ok(sqlx::query_as!(
+            CampaignLogEntry,
+            r#"
+SELECT kind, name
+FROM campaign_log_entries
+WHERE campaign_id = $1
+ORDER BY id
+"#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Record the result of a scenario and add to the campaign log
+    pub async fn record(
+        &self,
+        pool: &PgPool,
+        scenario_id: i64,
+        result: &CampaignResult,
+    ) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let campaign_scenario = sqlx::query!(
+            r#"
+INSERT INTO campaign_scenarios ( campaign_id, scenario_id, victory, threat_penalty )
+VALUES ( $1, $2, $3, $4 )
+RETURNING id
+"#,
+            self.id,
+            scenario_id,
+            result.victory,
+            result.threat_penalty
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        for (kind, name) in result.log_entries() {
+            sqlx::query!(
+                r#"
+INSERT INTO campaign_log_entries ( campaign_id, campaign_scenario_id, kind, name )
+VALUES ( $1, $2, $3, $4 )
+"#,
+                self.id,
+                campaign_scenario.id,
+                kind.as_str(),
+                name
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+UPDATE campaigns
+SET threat_penalty = threat_penalty + $2,
+    updated_at = CURRENT_TIMESTAMP
+WHERE id = $1
+"#,
+            self.id,
+            result.threat_penalty
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(id: i64) -> Scenario {
+        Scenario {
+            id,
+            title: format!("Scenario {}", id),
+            code: format!("{:0>4}", id),
+            set_id: 1,
+            number: Some(id as i16),
+            difficulty: None,
+        }
+    }
+
+    fn played(scenario_id: i64, victory: bool) -> CampaignScenario {
+        CampaignScenario {
+            scenario_id,
+            title: format!("Scenario {}", scenario_id),
+            victory,
+            threat_penalty: 0,
+        }
+    }
+
+    #[test]
+    fn it_finds_the_next_scenario() {
+        let scenarios = vec![scenario(1), scenario(2), scenario(3)];
+
+        assert_eq!(next_scenario(&scenarios, &[]).map(|s| s.id), Some(1));
+        assert_eq!(
+            next_scenario(&scenarios, &[played(1, true), played(2, false)]).map(|s| s.id),
+            Some(2)
+        );
+        assert!(next_scenario(
+            &scenarios,
+            &[played(1, true), played(2, true), played(3, true)]
+        )
+        .is_none());
+    }
+}
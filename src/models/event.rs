@@ -4,6 +4,65 @@ use sqlx::postgres::PgPool;
 pub struct Event {
     pub id: i64,
     pub name: String,
+    /// Seed for reproducible quest draws
+    pub seed: Option<String>,
+    /// RGB color of the event's embeds
+    pub color: Option<i32>,
+    /// Image shown in the corner of the event's embeds
+    pub thumbnail_url: Option<String>,
+}
+
+/// Where the event's pinned status message is
+pub struct StatusMessage {
+    pub channel_id: i64,
+    pub message_id: i64,
+}
+
+/// How many of a set's scenarios in the event are complete
+pub struct SetProgress {
+    pub name: String,
+    pub completed: i64,
+    pub total: i64,
+}
+
+/// A scenario that's checked out, and by whom
+pub struct Checkout {
+    pub title: String,
+    pub code: String,
+    pub user_name: Option<String>,
+    /// Minutes until the checkout runs out
+    pub minutes_left: i64,
+}
+
+/// What came of reporting one of the event's quests complete
+pub struct ScenarioCompletion {
+    /// Whether the quest is part of the event
+    pub found: bool,
+    /// Whether it's the first time the user reported it
+    pub credited: bool,
+}
+
+/// How many of the event's group challenges, those for a scenario, have been completed
+pub struct ChallengeProgress {
+    pub completed: i64,
+    pub total: i64,
+}
+
+impl ChallengeProgress {
+    pub fn percent(&self) -> Option<f64> {
+        if self.total == 0 {
+            None
+        } else {
+            Some((self.completed as f64 / self.total as f64) * 100.0)
+        }
+    }
+}
+
+/// A challenge as it's registered with an event
+pub struct EventChallenge {
+    pub challenge_event_id: i64,
+    pub name: String,
+    pub code: String,
 }
 
 impl Event {
@@ -12,7 +71,7 @@ impl Event {
         let mut rows = sqlx::query_as!(
             Event,
             r#"
-SELECT id, name
+SELECT id, name, seed, color, thumbnail_url
 FROM events
 WHERE active = $1
 "#,
@@ -29,7 +88,7 @@ WHERE active = $1
         Ok(sqlx::query_as!(
             Event,
             r#"
-SELECT id, name
+SELECT id, name, seed, color, thumbnail_url
 FROM events
 WHERE archive = $1
 "#,
@@ -49,60 +108,432 @@ WHERE archive = $1
         )
     }
 
-    /// Completed Challenges
-    pub async fn find_completed_challenges(&self, pool: &PgPool) -> anyhow::Result<Vec<Challenge>> {
-        let rows = sqlx::query!(
+    /// Set the seed used for quest draws. `None` clears it.
+    pub async fn update_seed(&self, pool: &PgPool, seed: Option<&str>) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+UPDATE events
+SET seed = $2,
+    updated_at = CURRENT_TIMESTAMP
+WHERE id = $1
+"#,
+            self.id,
+            seed
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+
+    /// Set the color and thumbnail of the event's embeds
+    pub async fn update_style(
+        &self,
+        pool: &PgPool,
+        color: Option<i32>,
+        thumbnail_url: Option<&str>,
+    ) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+UPDATE events
+SET color = $2,
+    thumbnail_url = $3,
+    updated_at = CURRENT_TIMESTAMP
+WHERE id = $1
+"#,
+            self.id,
+            color,
+            thumbnail_url
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+
+    /// Reserve a scenario for a user for 2 hours. Returns 0 if it's already reserved.
+    pub async fn checkout_scenario(
+        &self,
+        pool: &PgPool,
+        user_id: i64,
+        scenario_id: i64,
+    ) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+UPDATE events_scenarios
+SET checkout = CURRENT_TIMESTAMP,
+    checkout_user_id = $1,
+    updated_at = CURRENT_TIMESTAMP
+WHERE event_id = $2
+    AND scenario_id = $3
+    AND (checkout IS NULL OR checkout < CURRENT_TIMESTAMP - INTERVAL '2 hours')
+"#,
+            user_id,
+            self.id,
+            scenario_id,
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+
+    /// Mark a scenario as complete, crediting the user with it
+    pub async fn complete_scenario(
+        &self,
+        pool: &PgPool,
+        scenario_id: i64,
+        user_id: i64,
+    ) -> anyhow::Result<ScenarioCompletion> {
+        let mut tx = pool.begin().await?;
+
+        let found = sqlx::query!(
+            r#"
+UPDATE events_scenarios
+SET complete = true,
+    completed_at = COALESCE(completed_at, CURRENT_TIMESTAMP),
+    updated_at = CURRENT_TIMESTAMP
+WHERE event_id = $1
+    AND scenario_id = $2
+"#,
+            self.id,
+            scenario_id,
+        )
+        .execute(&mut tx)
+        .await?
+        .rows_affected()
+            > 0;
+
+        // everyone who reports the quest gets credit for it, even once the group has completed it
+        let credited = sqlx::query!(
+            r#"
+INSERT INTO events_scenarios_users ( events_scenarios_id, user_id )
+SELECT id, $3
+FROM events_scenarios
+WHERE event_id = $1
+    AND scenario_id = $2
+ON CONFLICT DO NOTHING
+"#,
+            self.id,
+            scenario_id,
+            user_id
+        )
+        .execute(&mut tx)
+        .await?
+        .rows_affected()
+            > 0;
+
+        tx.commit().await?;
+
+        Ok(ScenarioCompletion { found, credited })
+    }
+
+    /// Find the event whose pinned status message this is
+    pub async fn find_by_status_message(
+        pool: &PgPool,
+        message_id: u64,
+    ) -> anyhow::Result<Option<Event>> {
+        Ok(sqlx::query_as!(
+            Event,
+            r#"
+SELECT id, name, seed, color, thumbnail_url
+FROM events
+WHERE status_message_id = $1
+"#,
+            message_id as i64
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn find_status_message(
+        &self,
+        pool: &PgPool,
+    ) -> anyhow::Result<Option<StatusMessage>> {
+        Ok(sqlx::query_as!(
+            StatusMessage,
+            r#"
+SELECT status_channel_id AS "channel_id!", status_message_id AS "message_id!"
+FROM events
+WHERE id = $1
+    AND status_channel_id IS NOT NULL
+    AND status_message_id IS NOT NULL
+"#,
+            self.id
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn update_status_message(
+        &self,
+        pool: &PgPool,
+        channel_id: u64,
+        message_id: u64,
+    ) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+UPDATE events
+SET status_channel_id = $2,
+    status_message_id = $3,
+    updated_at = CURRENT_TIMESTAMP
+WHERE id = $1
+"#,
+            self.id,
+            channel_id as i64,
+            message_id as i64
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+
+    /// How much of each set in the event is complete, in set order
+    pub async fn find_set_progress(&self, pool: &PgPool) -> anyhow::Result<Vec<SetProgress>> {
+        Ok(sqlx::query_as!(
+            SetProgress,
+            r#"
+SELECT sets.name,
+    COUNT(*) FILTER (WHERE events_scenarios.complete = true) AS "completed!",
+    COUNT(*) AS "total!"
+FROM events_scenarios, scenarios, sets
+WHERE events_scenarios.event_id = $1
+    AND events_scenarios.scenario_id = scenarios.id
+    AND scenarios.set_id = sets.id
+GROUP BY sets.id, sets.name
+ORDER BY sets.id
+"#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// The scenarios checked out right now, soonest to run out first
+    pub async fn find_checkouts(&self, pool: &PgPool) -> anyhow::Result<Vec<Checkout>> {
+        Ok(sqlx::query_as!(
+            Checkout,
+            r#"
+SELECT scenarios.title,
+    scenarios.code,
+    users.name AS "user_name?",
+    CEIL(EXTRACT(EPOCH FROM (events_scenarios.checkout + INTERVAL '2 hours' - CURRENT_TIMESTAMP)) / 60)::BIGINT AS "minutes_left!"
+FROM events_scenarios
+    JOIN scenarios ON scenarios.id = events_scenarios.scenario_id
+    LEFT JOIN users ON users.id = events_scenarios.checkout_user_id
+WHERE events_scenarios.event_id = $1
+    AND events_scenarios.complete IS NOT TRUE
+    AND events_scenarios.checkout >= CURRENT_TIMESTAMP - INTERVAL '2 hours'
+ORDER BY events_scenarios.checkout
+"#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// The scenarios completed most recently, latest first
+    pub async fn find_recent_completions(
+        &self,
+        pool: &PgPool,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Scenario>> {
+        Ok(sqlx::query_as!(
+            Scenario,
             r#"
-SELECT challenges.id, challenges.name, challenges.code, challenges.description, scenarios.id AS scenario_id, scenarios.title AS scenario_title, scenarios.code AS scenario_code, scenarios.set_id AS scenario_set_id, scenarios.number AS scenario_number
+SELECT scenarios.id, scenarios.title, scenarios.code, scenarios.set_id, scenarios.number, scenarios.difficulty
+FROM scenarios, events_scenarios
+WHERE events_scenarios.scenario_id = scenarios.id
+    AND events_scenarios.event_id = $1
+    AND events_scenarios.complete = true
+ORDER BY events_scenarios.completed_at DESC NULLS LAST, events_scenarios.updated_at DESC
+LIMIT $2
+"#,
+            self.id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// The percentage of the event's scenarios that are complete
+    pub async fn find_progress(&self, pool: &PgPool) -> anyhow::Result<Option<f64>> {
+        Ok(sqlx::query!(
+            r#"
+SELECT (cnt/total::float)*100 AS perc
+FROM (
+    SELECT COUNT(*) AS total,
+        SUM(CASE WHEN complete = true THEN 1 ELSE 0 END) AS cnt
+    FROM events_scenarios
+    WHERE event_id = $1
+) x
+"#,
+            self.id
+        )
+        .fetch_one(pool)
+        .await?
+        .perc)
+    }
+
+    /// How many of the event's group challenges have been completed. Only challenges that are
+    /// active count as completed.
+    pub async fn find_group_challenge_progress(
+        &self,
+        pool: &PgPool,
+    ) -> anyhow::Result<ChallengeProgress> {
+        Ok(sqlx::query_as!(
+            ChallengeProgress,
+            r#"
+SELECT COUNT(challenges_events.id) FILTER (
+        WHERE (
+            challenges_events.active_date <= CURRENT_TIMESTAMP
+            OR challenges_events.active_date IS NULL
+        )
+        AND EXISTS (
+            SELECT 1
+            FROM challenges_events_users
+            WHERE challenges_events_users.challenges_events_id = challenges_events.id
+        )
+    ) AS "completed!",
+    COUNT(challenges_events.id) AS "total!"
 FROM challenges_events, challenges, scenarios
 WHERE challenges_events.event_id = $1
-    AND challenges_events.challenge_id = challenges.id
-    AND challenges.scenario_id = scenarios.id
-    AND (
-        challenges_events.active_date <= CURRENT_TIMESTAMP
-        OR challenges_events.active_date IS NULL
-    )
-    AND challenges.id IN (
-        SELECT challenges_events.challenge_id
-        FROM challenges_events_users, challenges_events
-        WHERE challenges_events_users.challenges_events_id = challenges_events.id
-            AND challenges_events.event_id = $1
-    )
-ORDER by challenges.code
+    AND challenges.id = challenges_events.challenge_id
+    AND scenarios.id = challenges.scenario_id
+"#,
+            self.id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// The percentage of the event's challenges with the attribute completed by anyone
+    pub async fn find_challenge_progress(
+        &self,
+        pool: &PgPool,
+        attribute: &str,
+    ) -> anyhow::Result<Option<f64>> {
+        Ok(sqlx::query!(
+            r#"
+WITH completed AS (
+    SELECT COUNT(DISTINCT(challenges_events_users.challenges_events_id)) AS cnt
+    FROM challenges_events_users, challenges_events, challenges
+    WHERE challenges_events.event_id = $1
+        AND challenges_events.id = challenges_events_users.challenges_events_id
+        AND challenges.id = challenges_events.challenge_id
+        AND $2 = ANY(challenges.attributes)
+), total AS (
+    SELECT COUNT(challenges_events.id) AS cnt
+    FROM challenges_events, challenges
+    WHERE challenges_events.event_id = $1
+        AND challenges.id = challenges_events.challenge_id
+        AND $2 = ANY(challenges.attributes)
+)
+SELECT (completed.cnt / NULLIF(total.cnt, 0)::float) * 100 AS perc
+FROM completed, total
+"#,
+            self.id,
+            attribute
+        )
+        .fetch_one(pool)
+        .await?
+        .perc)
+    }
+
+    /// All the scenarios registered with the event
+    pub async fn find_scenarios(&self, pool: &PgPool) -> anyhow::Result<Vec<Scenario>> {
+        Ok(sqlx::query_as!(
+            Scenario,
+            r#"
+SELECT scenarios.id, scenarios.title, scenarios.code, scenarios.set_id, scenarios.number, scenarios.difficulty
+FROM scenarios, events_scenarios
+WHERE events_scenarios.scenario_id = scenarios.id
+    AND events_scenarios.event_id = $1
+ORDER BY scenarios.code
 "#,
             self.id
         )
         .fetch_all(pool)
-        .await?;
+        .await?)
+    }
 
-        let challenges = rows
-            .into_iter()
-            .map(|row| {
-                let scenario = Scenario {
-                    id: row.scenario_id,
-                    title: row.scenario_title.clone(),
-                    code: row.scenario_code.clone(),
-                    set_id: row.scenario_set_id,
-                    number: row.scenario_number,
-                };
-                Challenge {
-                    id: row.id,
-                    name: row.name.clone(),
-                    code: row.code.clone(),
-                    description: row.description,
-                    scenario: Some(scenario),
-                }
-            })
-            .collect();
+    /// The event's scenarios that are still incomplete and not checked out
+    pub async fn find_open_scenarios(&self, pool: &PgPool) -> anyhow::Result<Vec<Scenario>> {
+        Ok(sqlx::query_as!(
+            Scenario,
+            r#"
+SELECT scenarios.id, scenarios.title, scenarios.code, scenarios.set_id, scenarios.number, scenarios.difficulty
+FROM scenarios, events_scenarios
+WHERE events_scenarios.scenario_id = scenarios.id
+    AND events_scenarios.event_id = $1
+    AND events_scenarios.complete = false
+    AND (events_scenarios.checkout IS NULL OR events_scenarios.checkout < CURRENT_TIMESTAMP - INTERVAL '2 hours')
+ORDER BY scenarios.code
+"#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
 
-        Ok(challenges)
+    /// The event's active challenges the user hasn't completed yet, optionally only those with an
+    /// attribute
+    pub async fn find_incomplete_challenges(
+        &self,
+        pool: &PgPool,
+        discord_id: u64,
+        attribute: Option<&str>,
+    ) -> anyhow::Result<Vec<EventChallenge>> {
+        Ok(sqlx::query_as!(
+            EventChallenge,
+            r#"
+SELECT challenges_events.id AS challenge_event_id, challenges.name, challenges.code
+FROM challenges, challenges_events
+WHERE challenges_events.challenge_id = challenges.id
+    AND challenges_events.event_id = $1
+    AND ($2::VARCHAR IS NULL OR $2 = ANY(challenges.attributes))
+    AND (challenges_events.active_date IS NULL OR challenges_events.active_date <= CURRENT_TIMESTAMP)
+    AND challenges_events.id NOT IN (
+        SELECT challenges_events_users.challenges_events_id
+        FROM challenges_events_users, users
+        WHERE challenges_events_users.user_id = users.id
+            AND users.discord_id = $3
+    )
+ORDER BY challenges.code
+"#,
+            self.id,
+            attribute,
+            discord_id as i64
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// All the challenges registered with the event, optionally only those with an attribute
+    pub async fn find_challenges(
+        &self,
+        pool: &PgPool,
+        attribute: Option<&str>,
+    ) -> anyhow::Result<Vec<EventChallenge>> {
+        Ok(sqlx::query_as!(
+            EventChallenge,
+            r#"
+SELECT challenges_events.id AS challenge_event_id, challenges.name, challenges.code
+FROM challenges, challenges_events
+WHERE challenges_events.challenge_id = challenges.id
+    AND challenges_events.event_id = $1
+    AND ($2::VARCHAR IS NULL OR $2 = ANY(challenges.attributes))
+ORDER BY challenges.code
+"#,
+            self.id,
+            attribute
+        )
+        .fetch_all(pool)
+        .await?)
     }
 
     /// Fetch all challenges
     pub async fn find_all_challenges(&self, pool: &PgPool) -> anyhow::Result<Vec<Challenge>> {
         let rows = sqlx::query!(
             r#"
-SELECT challenges.id, challenges.name, challenges.code, challenges.description, scenarios.id AS scenario_id, scenarios.title AS scenario_title, scenarios.code AS scenario_code, scenarios.set_id AS scenario_set_id, scenarios.number AS scenario_number
+SELECT challenges.id, challenges.name, challenges.code, challenges.description, scenarios.id AS scenario_id, scenarios.title AS scenario_title, scenarios.code AS scenario_code, scenarios.set_id AS scenario_set_id, scenarios.number AS scenario_number, scenarios.difficulty AS scenario_difficulty
 FROM challenges_events, challenges, scenarios
 WHERE challenges_events.challenge_id = challenges.id
     AND challenges.scenario_id = scenarios.id
@@ -124,6 +555,7 @@ ORDER BY challenges.code
                     code: row.scenario_code.clone(),
                     set_id: row.scenario_set_id,
                     number: row.scenario_number,
+                    difficulty: row.scenario_difficulty,
                 };
                 Challenge {
                     id: row.id,
@@ -145,7 +577,7 @@ ORDER BY challenges.code
     ) -> anyhow::Result<Vec<Challenge>> {
         let rows = sqlx::query!(
             r#"
-SELECT challenges.id, challenges.name, challenges.code, challenges.description, scenarios.id AS scenario_id, scenarios.title AS scenario_title, scenarios.code AS scenario_code, scenarios.set_id AS scenario_set_id, scenarios.number AS scenario_number
+SELECT challenges.id, challenges.name, challenges.code, challenges.description, scenarios.id AS scenario_id, scenarios.title AS scenario_title, scenarios.code AS scenario_code, scenarios.set_id AS scenario_set_id, scenarios.number AS scenario_number, scenarios.difficulty AS scenario_difficulty
 FROM challenges_events, challenges, scenarios
 WHERE challenges_events.challenge_id = challenges.id
     AND challenges.scenario_id = scenarios.id
@@ -171,6 +603,7 @@ ORDER BY challenges.code
                     code: row.scenario_code.clone(),
                     set_id: row.scenario_set_id,
                     number: row.scenario_number,
+                    difficulty: row.scenario_difficulty,
                 };
                 Challenge {
                     id: row.id,
@@ -192,7 +625,7 @@ ORDER BY challenges.code
     ) -> anyhow::Result<Vec<Challenge>> {
         let rows = sqlx::query!(
             r#"
-SELECT challenges.id, challenges.name, challenges.code, challenges.description, scenarios.id AS scenario_id, scenarios.title AS scenario_title, scenarios.code AS scenario_code, scenarios.set_id AS scenario_set_id, scenarios.number AS scenario_number
+SELECT challenges.id, challenges.name, challenges.code, challenges.description, scenarios.id AS scenario_id, scenarios.title AS scenario_title, scenarios.code AS scenario_code, scenarios.set_id AS scenario_set_id, scenarios.number AS scenario_number, scenarios.difficulty AS scenario_difficulty
 FROM challenges_events, challenges, scenarios
 WHERE challenges_events.challenge_id = challenges.id
     AND challenges.scenario_id = scenarios.id
@@ -224,6 +657,7 @@ ORDER BY challenges.code
                     code: row.scenario_code.clone(),
                     set_id: row.scenario_set_id,
                     number: row.scenario_number,
+                    difficulty: row.scenario_difficulty,
                 };
                 Challenge {
                     id: row.id,
@@ -0,0 +1,158 @@
+use crate::models::user::User;
+use sqlx::postgres::PgPool;
+
+/// An attempt at a gauntlet of stacked modifiers. It's open until its result is recorded.
+pub struct GauntletRun {
+    pub id: i64,
+    pub seed: String,
+}
+
+/// A gauntlet that was beaten, for the hall of fame
+pub struct BeatenGauntlet {
+    pub scenario_title: Option<String>,
+    pub modifiers: Vec<String>,
+    pub players: Vec<String>,
+}
+
+impl GauntletRun {
+    /// Save a newly drawn gauntlet along with everyone attempting it
+    pub async fn create(
+        pool: &PgPool,
+        scenario_id: Option<i64>,
+        seed: &str,
+        challenge_ids: &[i64],
+        user_ids: &[i64],
+    ) -> anyhow::Result<GauntletRun> {
+        let mut tx = pool.begin().await?;
+
+        let run = sqlx::query_as!(
+            GauntletRun,
+            r#"
+INSERT INTO gauntlet_runs ( scenario_id, seed )
+VALUES ( $1, $2 )
+RETURNING id, seed
+"#,
+            scenario_id,
+            seed
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+INSERT INTO gauntlet_runs_challenges ( gauntlet_run_id, challenge_id )
+SELECT $1, UNNEST($2::BIGINT[])
+ON CONFLICT DO NOTHING
+"#,
+            run.id,
+            challenge_ids
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+INSERT INTO gauntlet_runs_users ( gauntlet_run_id, user_id )
+SELECT $1, UNNEST($2::BIGINT[])
+ON CONFLICT DO NOTHING
+"#,
+            run.id,
+            user_ids
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(run)
+    }
+
+    /// Find the latest gauntlet a user is attempting that doesn't have a result yet
+    pub async fn find_open_by_user(
+        pool: &PgPool,
+        user_id: i64,
+    ) -> anyhow::Result<Option<GauntletRun>> {
+        Ok(sqlx::query_as!(
+            GauntletRun,
+            r#"
+SELECT gauntlet_runs.id, gauntlet_runs.seed
+FROM gauntlet_runs, gauntlet_runs_users
+WHERE gauntlet_runs_users.gauntlet_run_id = gauntlet_runs.id
+    AND gauntlet_runs_users.user_id = $1
+    AND gauntlet_runs.victory IS NULL
+ORDER BY gauntlet_runs.created_at DESC, gauntlet_runs.id DESC
+LIMIT 1
+"#,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Everyone attempting the gauntlet
+    pub async fn find_users(&self, pool: &PgPool) -> anyhow::Result<Vec<User>> {
+        Ok(sqlx::query_as!(
+            User,
+            r#"
+SELECT users.id, users.discord_id, users.name
+FROM users, gauntlet_runs_users
+WHERE gauntlet_runs_users.gauntlet_run_id = $1
+    AND users.id = gauntlet_runs_users.user_id
+ORDER BY gauntlet_runs_users.id
+"#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Record whether the gauntlet was survived
+    pub async fn close(&self, pool: &PgPool, victory: bool) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+UPDATE gauntlet_runs
+SET victory = $2,
+    updated_at = CURRENT_TIMESTAMP
+WHERE id = $1
+"#,
+            self.id,
+            victory
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+
+    /// The hardest gauntlets beaten, ranked by how many modifiers were stacked. Ties go to whoever
+    /// beat it first.
+    pub async fn find_fame(pool: &PgPool, limit: i64) -> anyhow::Result<Vec<BeatenGauntlet>> {
+        Ok(sqlx::query_as!(
+            BeatenGauntlet,
+            r#"
+SELECT scenarios.title AS "scenario_title?", (
+    SELECT ARRAY_AGG(challenges.name ORDER BY challenges.code)
+    FROM gauntlet_runs_challenges, challenges
+    WHERE gauntlet_runs_challenges.gauntlet_run_id = gauntlet_runs.id
+        AND gauntlet_runs_challenges.challenge_id = challenges.id
+) AS "modifiers!", (
+    SELECT ARRAY_AGG(COALESCE(users.name, 'Unknown') ORDER BY users.name)
+    FROM gauntlet_runs_users, users
+    WHERE gauntlet_runs_users.gauntlet_run_id = gauntlet_runs.id
+        AND gauntlet_runs_users.user_id = users.id
+) AS "players!"
+FROM gauntlet_runs
+LEFT JOIN scenarios ON scenarios.id = gauntlet_runs.scenario_id
+WHERE gauntlet_runs.victory = true
+ORDER BY (
+    SELECT COUNT(*)
+    FROM gauntlet_runs_challenges
+    WHERE gauntlet_runs_challenges.gauntlet_run_id = gauntlet_runs.id
+) DESC, gauntlet_runs.updated_at
+LIMIT $1
+"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+}
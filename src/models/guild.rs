@@ -0,0 +1,48 @@
+use sqlx::postgres::PgPool;
+
+/// Settings a Discord server has picked for how the bot behaves there
+pub struct GuildSettings {
+    /// Reply with plain text instead of embeds
+    pub plain_text: bool,
+}
+
+impl GuildSettings {
+    /// Find the server's settings, or the defaults when it hasn't changed any
+    pub async fn find(pool: &PgPool, guild_id: u64) -> anyhow::Result<GuildSettings> {
+        Ok(sqlx::query_as!(
+            GuildSettings,
+            r#"
+SELECT plain_text
+FROM guild_settings
+WHERE guild_id = $1
+"#,
+            guild_id as i64
+        )
+        .fetch_optional(pool)
+        .await?
+        .unwrap_or(GuildSettings { plain_text: false }))
+    }
+
+    pub async fn update_plain_text(
+        pool: &PgPool,
+        guild_id: u64,
+        plain_text: bool,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+INSERT INTO guild_settings ( guild_id, plain_text )
+VALUES ( $1, $2 )
+ON CONFLICT (guild_id)
+DO
+    UPDATE SET plain_text = $2,
+        updated_at = CURRENT_TIMESTAMP
+"#,
+            guild_id as i64,
+            plain_text
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use sqlx::postgres::PgPool;
+use std::fmt;
+
+/// The group progress a milestone is for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MilestoneKind {
+    /// The event's quests, as in `!event progress`
+    Quests,
+    /// The event's group challenges
+    Challenges,
+    /// The Council of 100 Kangs, as in `!event kang progress`
+    Kangs,
+}
+
+impl MilestoneKind {
+    pub const ALL: [MilestoneKind; 3] = [
+        MilestoneKind::Quests,
+        MilestoneKind::Challenges,
+        MilestoneKind::Kangs,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MilestoneKind::Quests => "quests",
+            MilestoneKind::Challenges => "challenges",
+            MilestoneKind::Kangs => "kangs",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<MilestoneKind> {
+        MilestoneKind::ALL
+            .iter()
+            .copied()
+            .find(|known| known.as_str().eq_ignore_ascii_case(kind))
+    }
+}
+
+impl fmt::Display for MilestoneKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A share of the event's progress to announce once the group gets there
+pub struct Milestone {
+    pub kind: String,
+    pub percent: i16,
+    /// Role to ping with the announcement
+    pub role_id: Option<i64>,
+    pub announced: bool,
+}
+
+impl Milestone {
+    pub async fn find_by_event(pool: &PgPool, event_id: i64) -> anyhow::Result<Vec<Milestone>> {
+        Ok(sqlx::query_as!(
+            Milestone,
+            r#"
+SELECT kind, percent, role_id, announced_at IS NOT NULL AS "announced!"
+FROM event_milestones
+WHERE event_id = $1
+ORDER BY kind, percent
+"#,
+            event_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Set the event's milestones for the kind of progress. Milestones that were already there
+    /// keep whether they've been announced.
+    pub async fn replace(
+        pool: &PgPool,
+        event_id: i64,
+        kind: MilestoneKind,
+        percents: &[i16],
+        role_id: Option<u64>,
+    ) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+DELETE FROM event_milestones
+WHERE event_id = $1
+    AND kind = $2
+    AND percent <> ALL($3::SMALLINT[])
+"#,
+            event_id,
+            kind.as_str(),
+            percents
+        )
+        .execute(&mut tx)
+        .await?;
+
+        for percent in percents {
+            sqlx::query!(
+                r#"
+INSERT INTO event_milestones ( event_id, kind, percent, role_id )
+VALUES ( $1, $2, $3, $4 )
+ON CONFLICT (event_id, kind, percent)
+DO
+    UPDATE SET role_id = $4,
+        updated_at = CURRENT_TIMESTAMP
+"#,
+                event_id,
+                kind.as_str(),
+                percent,
+                role_id.map(|role_id| role_id as i64)
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Mark every milestone the progress has reached as announced, returning the ones that
+    /// weren't yet. Only one caller gets each milestone, however many check at once.
+    pub async fn claim_reached(
+        pool: &PgPool,
+        event_id: i64,
+        kind: MilestoneKind,
+        progress: f64,
+    ) -> anyhow::Result<Vec<Milestone>> {
+        let mut milestones = sqlx::query_as!(
+            Milestone,
+            r#"
+UPDATE event_milestones
+SET announced_at = CURRENT_TIMESTAMP,
+    updated_at = CURRENT_TIMESTAMP
+WHERE event_id = $1
+    AND kind = $2
+    AND percent <= $3::FLOAT8
+    AND announced_at IS NULL
+RETURNING kind, percent, role_id, true AS "announced!"
+"#,
+            event_id,
+            kind.as_str(),
+            progress
+        )
+        .fetch_all(pool)
+        .await?;
+        milestones.sort_by_key(|milestone| milestone.percent);
+
+        Ok(milestones)
+    }
+}
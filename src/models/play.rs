@@ -0,0 +1,135 @@
+use sqlx::postgres::PgPool;
+use std::{fmt, str::FromStr};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Nightmare,
+}
+
+impl Difficulty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Nightmare => "nightmare",
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "nightmare" => Ok(Difficulty::Nightmare),
+            _ => Err(anyhow::anyhow!(
+                "The only valid modes are 'easy', 'normal', 'nightmare'."
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A single recorded game
+pub struct Play {
+    pub user_id: i64,
+    pub scenario_id: i64,
+    pub event_id: Option<i64>,
+    pub victory: bool,
+    pub players: Option<i16>,
+    pub difficulty: Difficulty,
+    pub notes: Option<String>,
+}
+
+/// Aggregated results for a scenario
+pub struct PlayStats {
+    pub attempts: i64,
+    pub wins: i64,
+}
+
+impl PlayStats {
+    /// Percentage of attempts that were won
+    pub fn win_rate(&self) -> f32 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            (self.wins as f32 / self.attempts as f32) * 100.0
+        }
+    }
+}
+
+impl Play {
+    /// Record a play
+    pub async fn create(&self, pool: &PgPool) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+INSERT INTO plays ( user_id, scenario_id, event_id, victory, players, difficulty, notes )
+VALUES ( $1, $2, $3, $4, $5, $6, $7 )
+"#,
+            self.user_id,
+            self.scenario_id,
+            self.event_id,
+            self.victory,
+            self.players,
+            self.difficulty.as_str(),
+            self.notes
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+
+    /// Results across everyone who has played a scenario
+    pub async fn stats_by_scenario(pool: &PgPool, scenario_id: i64) -> anyhow::Result<PlayStats> {
+        let row = sqlx::query!(
+            r#"
+SELECT COUNT(*) AS "attempts!", COUNT(*) FILTER (WHERE victory) AS "wins!"
+FROM plays
+WHERE scenario_id = $1
+"#,
+            scenario_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(PlayStats {
+            attempts: row.attempts,
+            wins: row.wins,
+        })
+    }
+
+    /// Results for a single user on a scenario
+    pub async fn stats_by_user_and_scenario(
+        pool: &PgPool,
+        user_id: i64,
+        scenario_id: i64,
+    ) -> anyhow::Result<PlayStats> {
+        let row = sqlx::query!(
+            r#"
+SELECT COUNT(*) AS "attempts!", COUNT(*) FILTER (WHERE victory) AS "wins!"
+FROM plays
+WHERE user_id = $1
+    AND scenario_id = $2
+"#,
+            user_id,
+            scenario_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(PlayStats {
+            attempts: row.attempts,
+            wins: row.wins,
+        })
+    }
+}
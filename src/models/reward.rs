@@ -0,0 +1,197 @@
+use sqlx::postgres::PgPool;
+
+/// What a player has to complete during the event to earn a reward
+#[derive(Debug, PartialEq)]
+pub enum RewardRule {
+    /// At least this many of the event's challenges
+    Challenges(i32),
+    /// Every scenario the event has from the set
+    Set(i64),
+}
+
+/// A role handed out to the players who meet its rule
+pub struct Reward {
+    pub role_id: i64,
+    pub challenges: Option<i32>,
+    pub set_name: Option<String>,
+    /// How many players have been given the role
+    pub granted: i64,
+}
+
+/// A reward a player has earned but hasn't been given yet
+pub struct EarnedReward {
+    pub reward_id: i64,
+    pub role_id: i64,
+    pub user_id: i64,
+    pub discord_id: i64,
+}
+
+impl Reward {
+    pub async fn find_by_event(pool: &PgPool, event_id: i64) -> anyhow::Result<Vec<Reward>> {
+        Ok(sqlx::query_as!(
+            Reward,
+            r#"
+SELECT event_rewards.role_id,
+    event_rewards.challenges,
+    sets.name AS "set_name?",
+    COUNT(event_reward_grants.id) AS "granted!"
+FROM event_rewards
+LEFT JOIN sets ON sets.id = event_rewards.set_id
+LEFT JOIN event_reward_grants ON event_reward_grants.reward_id = event_rewards.id
+WHERE event_rewards.event_id = $1
+GROUP BY event_rewards.id, sets.name
+ORDER BY event_rewards.id
+"#,
+            event_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Set the rule for earning the role, replacing the one it had. Players who were given the
+    /// role already keep it.
+    pub async fn replace(
+        pool: &PgPool,
+        event_id: i64,
+        role_id: u64,
+        rule: &RewardRule,
+    ) -> anyhow::Result<()> {
+        let (challenges, set_id) = match rule {
+            RewardRule::Challenges(challenges) => (Some(*challenges), None),
+            RewardRule::Set(set_id) => (None, Some(*set_id)),
+        };
+
+        sqlx::query!(
+            r#"
+INSERT INTO event_rewards ( event_id, role_id, challenges, set_id )
+VALUES ( $1, $2, $3, $4 )
+ON CONFLICT (event_id, role_id)
+DO
+    UPDATE SET challenges = $3,
+        set_id = $4,
+        updated_at = CURRENT_TIMESTAMP
+"#,
+            event_id,
+            role_id as i64,
+            challenges,
+            set_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stop handing out the role, forgetting who it was given to
+    pub async fn delete(pool: &PgPool, event_id: i64, role_id: u64) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+DELETE FROM event_rewards
+WHERE event_id = $1
+    AND role_id = $2
+"#,
+            event_id,
+            role_id as i64
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+
+    /// Find the rewards the players have earned in the event but haven't been given yet
+    pub async fn find_earned(
+        pool: &PgPool,
+        event_id: i64,
+        discord_ids: &[i64],
+    ) -> anyhow::Result<Vec<EarnedReward>> {
+        Ok(sqlx::query_as!(
+            EarnedReward,
+            r#"
+SELECT event_rewards.id AS reward_id,
+    event_rewards.role_id,
+    users.id AS user_id,
+    users.discord_id
+FROM event_rewards, users
+WHERE event_rewards.event_id = $1
+    AND users.discord_id = ANY($2)
+    AND NOT EXISTS (
+        SELECT 1
+        FROM event_reward_grants
+        WHERE event_reward_grants.reward_id = event_rewards.id
+            AND event_reward_grants.user_id = users.id
+    )
+    AND (
+        (
+            SELECT COUNT(challenges_events_users.id)
+            FROM challenges_events_users, challenges_events
+            WHERE challenges_events.event_id = $1
+                AND challenges_events.id = challenges_events_users.challenges_events_id
+                AND challenges_events_users.user_id = users.id
+        ) >= event_rewards.challenges
+        OR (
+            EXISTS (
+                SELECT 1
+                FROM events_scenarios, scenarios
+                WHERE events_scenarios.event_id = $1
+                    AND scenarios.id = events_scenarios.scenario_id
+                    AND scenarios.set_id = event_rewards.set_id
+            )
+            AND NOT EXISTS (
+                SELECT 1
+                FROM events_scenarios, scenarios
+                WHERE events_scenarios.event_id = $1
+                    AND scenarios.id = events_scenarios.scenario_id
+                    AND scenarios.set_id = event_rewards.set_id
+                    AND NOT EXISTS (
+                        SELECT 1
+                        FROM events_scenarios_users
+                        WHERE events_scenarios_users.events_scenarios_id = events_scenarios.id
+                            AND events_scenarios_users.user_id = users.id
+                    )
+            )
+        )
+    )
+ORDER BY event_rewards.id
+"#,
+            event_id,
+            discord_ids
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Record that the player is being given the reward. Only one caller gets to claim each, so
+    /// it's never given twice.
+    pub async fn claim(pool: &PgPool, reward_id: i64, user_id: i64) -> anyhow::Result<bool> {
+        Ok(sqlx::query!(
+            r#"
+INSERT INTO event_reward_grants ( reward_id, user_id )
+VALUES ( $1, $2 )
+ON CONFLICT DO NOTHING
+"#,
+            reward_id,
+            user_id
+        )
+        .execute(pool)
+        .await?
+        .rows_affected()
+            > 0)
+    }
+
+    /// Forget a claim on a reward that couldn't be given, so it's tried again later
+    pub async fn unclaim(pool: &PgPool, reward_id: i64, user_id: i64) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+DELETE FROM event_reward_grants
+WHERE reward_id = $1
+    AND user_id = $2
+"#,
+            reward_id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::challenge::Challenge;
 use sqlx::postgres::PgPool;
 use std::cmp::Ordering;
 
@@ -8,6 +9,7 @@ pub struct Scenario {
     pub code: String,
     pub set_id: i64,
     pub number: Option<i16>,
+    pub difficulty: Option<i16>,
 }
 
 impl Ord for Scenario {
@@ -23,16 +25,60 @@ impl PartialOrd for Scenario {
 }
 
 impl Scenario {
-    // this is used by  src/bin/load_challenges.rs
-    #[allow(dead_code)]
-    /// Find a scenaario by title
+    /// Find a scenario by code
+    pub async fn find_by_code(pool: &PgPool, code: &str) -> anyhow::Result<Option<Scenario>> {
+        Ok(sqlx::query_as!(
+            Scenario,
+            r#"
+SELECT id, title, code, set_id, number, difficulty
+FROM scenarios
+WHERE code = $1
+"#,
+            code
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Find a scenario by one of its aliases, ignoring case
+    pub async fn find_by_alias(pool: &PgPool, alias: &str) -> anyhow::Result<Option<Scenario>> {
+        Ok(sqlx::query_as!(
+            Scenario,
+            r#"
+SELECT scenarios.id, scenarios.title, scenarios.code, scenarios.set_id, scenarios.number, scenarios.difficulty
+FROM scenarios, scenario_aliases
+WHERE scenario_aliases.scenario_id = scenarios.id
+    AND LOWER(scenario_aliases.alias) = LOWER($1)
+"#,
+            alias
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Find a scenario by alias, falling back to the code
+    pub async fn find_by_alias_or_code(
+        pool: &PgPool,
+        query: &str,
+    ) -> anyhow::Result<Option<Scenario>> {
+        match Scenario::find_by_alias(pool, query).await? {
+            Some(scenario) => Ok(Some(scenario)),
+            None => Scenario::find_by_code(pool, query).await,
+        }
+    }
+
+    /// Find a scenaario by alias or title, ignoring case
     pub async fn find_by_title(pool: &PgPool, title: &str) -> anyhow::Result<Option<Scenario>> {
+        if let Some(scenario) = Scenario::find_by_alias(pool, title).await? {
+            return Ok(Some(scenario));
+        }
+
         let mut scenarios = sqlx::query_as!(
             Scenario,
             r#"
-SELECT id, title, code, set_id, number
+SELECT id, title, code, set_id, number, difficulty
 FROM scenarios
-WHERE title = $1
+WHERE LOWER(title) = LOWER($1)
 "#,
             title
         )
@@ -41,4 +87,81 @@ WHERE title = $1
 
         Ok(scenarios.pop())
     }
+
+    /// Find all the scenarios in a set, in order
+    pub async fn find_by_set(pool: &PgPool, set_id: i64) -> anyhow::Result<Vec<Scenario>> {
+        Ok(sqlx::query_as!(
+            Scenario,
+            r#"
+SELECT id, title, code, set_id, number, difficulty
+FROM scenarios
+WHERE set_id = $1
+ORDER BY number, code
+"#,
+            set_id
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Add an alias for the scenario, moving it over if another scenario already had it
+    pub async fn add_alias(&self, pool: &PgPool, alias: &str) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+INSERT INTO scenario_aliases ( scenario_id, alias )
+VALUES ( $1, $2 )
+ON CONFLICT (LOWER(alias)) DO UPDATE
+SET scenario_id = EXCLUDED.scenario_id,
+    alias = EXCLUDED.alias,
+    updated_at = CURRENT_TIMESTAMP
+"#,
+            self.id,
+            alias
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_aliases(&self, pool: &PgPool) -> anyhow::Result<Vec<String>> {
+        Ok(sqlx::query!(
+            r#"
+SELECT alias
+FROM scenario_aliases
+WHERE scenario_id = $1
+ORDER BY alias
+"#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| row.alias)
+        .collect())
+    }
+
+    /// Find the challenges attached to the scenario
+    pub async fn find_challenges(&self, pool: &PgPool) -> anyhow::Result<Vec<Challenge>> {
+        Ok(sqlx::query!(
+            r#"
+SELECT id, name, code, description
+FROM challenges
+WHERE scenario_id = $1
+ORDER BY code
+"#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| Challenge {
+            id: row.id,
+            name: row.name,
+            code: row.code,
+            description: row.description,
+            scenario: Some(self.clone()),
+        })
+        .collect())
+    }
 }
@@ -0,0 +1,103 @@
+use sqlx::postgres::PgPool;
+
+/// The values needed to calculate the official score of a game
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ScoreSheet {
+    pub final_threat: i32,
+    pub dead_heroes_threat: i32,
+    pub damage: i32,
+    pub rounds: i32,
+    pub victory_points: i32,
+}
+
+impl ScoreSheet {
+    /// Official scoring: final threat + threat cost of dead heroes + damage on heroes + 10 × rounds
+    /// − victory points. Lower is better.
+    pub fn score(&self) -> i32 {
+        self.final_threat + self.dead_heroes_threat + self.damage + 10 * self.rounds
+            - self.victory_points
+    }
+}
+
+/// A row on the leaderboard
+pub struct BestScore {
+    pub name: Option<String>,
+    pub score: i32,
+    pub rounds: i32,
+}
+
+pub struct Score;
+
+impl Score {
+    /// Record a score
+    pub async fn create(
+        pool: &PgPool,
+        user_id: i64,
+        scenario_id: i64,
+        event_id: Option<i64>,
+        sheet: &ScoreSheet,
+    ) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+INSERT INTO scores ( user_id, scenario_id, event_id, final_threat, dead_heroes_threat, damage, rounds, victory_points, score )
+VALUES ( $1, $2, $3, $4, $5, $6, $7, $8, $9 )
+"#,
+            user_id,
+            scenario_id,
+            event_id,
+            sheet.final_threat,
+            sheet.dead_heroes_threat,
+            sheet.damage,
+            sheet.rounds,
+            sheet.victory_points,
+            sheet.score()
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+
+    /// Lowest scores for a scenario in an event
+    pub async fn find_best(
+        pool: &PgPool,
+        event_id: i64,
+        scenario_id: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<BestScore>> {
+        Ok(sqlx::query_as!(
+            BestScore,
+            r#"
+SELECT users.name, scores.score, scores.rounds
+FROM scores, users
+WHERE scores.event_id = $1
+    AND scores.scenario_id = $2
+    AND scores.user_id = users.id
+ORDER BY scores.score, scores.created_at
+LIMIT $3
+"#,
+            event_id,
+            scenario_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_calculates_the_official_score() {
+        let sheet = ScoreSheet {
+            final_threat: 38,
+            dead_heroes_threat: 9,
+            damage: 4,
+            rounds: 7,
+            victory_points: 5,
+        };
+
+        assert_eq!(sheet.score(), 116);
+    }
+}
@@ -1,8 +1,72 @@
 use sqlx::postgres::PgPool;
+use std::{fmt, str::FromStr};
 
+/// The kind of product a set was released as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProductType {
+    Core,
+    Deluxe,
+    AdventurePack,
+    Saga,
+    Standalone,
+    Starter,
+}
+
+impl ProductType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProductType::Core => "core",
+            ProductType::Deluxe => "deluxe",
+            ProductType::AdventurePack => "adventure_pack",
+            ProductType::Saga => "saga",
+            ProductType::Standalone => "standalone",
+            ProductType::Starter => "starter",
+        }
+    }
+}
+
+impl FromStr for ProductType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', ' '], "_").as_str() {
+            "core" | "core_set" => Ok(ProductType::Core),
+            "deluxe" => Ok(ProductType::Deluxe),
+            "adventure_pack" | "pack" | "ap" => Ok(ProductType::AdventurePack),
+            "saga" => Ok(ProductType::Saga),
+            "standalone" => Ok(ProductType::Standalone),
+            "starter" => Ok(ProductType::Starter),
+            _ => Err(anyhow::anyhow!(
+                "The only valid product types are 'core', 'deluxe', 'adventure_pack', 'saga', 'standalone', 'starter'."
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ProductType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Clone)]
 pub struct Set {
     pub id: i64,
     pub name: String,
+    pub product_type: Option<String>,
+    pub cycle: Option<String>,
+}
+
+impl fmt::Display for Set {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        match (&self.product_type, &self.cycle) {
+            (Some(product_type), Some(cycle)) => write!(f, " ({}, {})", product_type, cycle),
+            (Some(product_type), None) => write!(f, " ({})", product_type),
+            (None, Some(cycle)) => write!(f, " ({})", cycle),
+            (None, None) => Ok(()),
+        }
+    }
 }
 
 impl Set {
@@ -10,11 +74,90 @@ impl Set {
         Ok(sqlx::query_as!(
             Set,
             r#"
-SELECT id, name
+SELECT id, name, product_type, cycle
+FROM sets
+ORDER BY id
+"#,
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: i64) -> anyhow::Result<Set> {
+        Ok(sqlx::query_as!(
+            Set,
+            r#"
+SELECT id, name, product_type, cycle
+FROM sets
+WHERE id = $1
+"#,
+            id
+        )
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Find a set by name, ignoring case
+    pub async fn find_by_name(pool: &PgPool, name: &str) -> anyhow::Result<Option<Set>> {
+        Ok(sqlx::query_as!(
+            Set,
+            r#"
+SELECT id, name, product_type, cycle
 FROM sets
+WHERE LOWER(name) = LOWER($1)
+"#,
+            name
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Find all the sets a user owns
+    pub async fn find_by_owner(pool: &PgPool, user_id: i64) -> anyhow::Result<Vec<Set>> {
+        Ok(sqlx::query_as!(
+            Set,
+            r#"
+SELECT sets.id, sets.name, sets.product_type, sets.cycle
+FROM sets, user_sets
+WHERE user_sets.set_id = sets.id
+    AND user_sets.user_id = $1
+ORDER BY sets.id
 "#,
+            user_id
         )
         .fetch_all(pool)
         .await?)
     }
+
+    /// Add the set to a user's collection
+    pub async fn add_owner(&self, pool: &PgPool, user_id: i64) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+INSERT INTO user_sets ( user_id, set_id )
+VALUES ( $1, $2 )
+ON CONFLICT DO NOTHING
+"#,
+            user_id,
+            self.id
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+
+    /// Remove the set from a user's collection
+    pub async fn remove_owner(&self, pool: &PgPool, user_id: i64) -> anyhow::Result<u64> {
+        Ok(sqlx::query!(
+            r#"
+DELETE FROM user_sets
+WHERE user_id = $1
+    AND set_id = $2
+"#,
+            user_id,
+            self.id
+        )
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
 }
@@ -33,7 +33,7 @@ DO
         sqlx::query_as!(
             crate::models::event::Event,
             r#"
-SELECT id, name
+SELECT id, name, seed, color, thumbnail_url
 FROM events
 WHERE active = $1
 "#,
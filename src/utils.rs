@@ -1,90 +1,513 @@
-use crate::models::{challenge::Challenge, scenario::Scenario, set::Set};
+use crate::{fuzzy, models::set::Set};
 use serenity::{
-    model::channel::Message, prelude::Context, prelude::TypeMapKey, utils::MessageBuilder,
+    builder::{CreateActionRow, CreateComponents},
+    model::{
+        channel::Message,
+        id::ChannelId,
+        interactions::{message_component::ButtonStyle, InteractionResponseType},
+    },
+    prelude::Context,
+    prelude::TypeMapKey,
 };
 use std::time::Duration;
 use tracing::error;
 
 pub const SELECTION_TIMEOUT: u64 = 60;
+/// Number of options on each page of a picker. Discord allows at most 25 in a select menu.
+pub const PICKER_PAGE_SIZE: usize = 25;
+/// Discord cuts off select menu labels at 100 characters
+const MAX_LABEL_LENGTH: usize = 100;
+/// Discord rejects messages longer than 2000 characters
+pub const MESSAGE_LIMIT: usize = 2000;
+/// Room left on each browsed page for its page number
+const PAGE_FOOTER_LENGTH: usize = 32;
 
 pub struct PostgresPool;
 impl TypeMapKey for PostgresPool {
     type Value = sqlx::postgres::PgPool;
 }
 
-/// Format collection into a 1-indexed joint String
-pub fn format_collection<T: std::fmt::Display>(collection: &Vec<T>) -> String {
+/// Format each item of a collection as a 1-indexed line
+pub fn number_collection<T: std::fmt::Display>(collection: &[T]) -> Vec<String> {
     let width = collection.len() / 10;
     collection
         .iter()
         .enumerate()
         .map(|(i, item)| format!("{:>width$}.) {}", i + 1, item, width = width))
-        .collect::<Vec<String>>()
-        .join("\n")
+        .collect()
 }
 
-/// Format collection into a buckets of 1-indexed joint String. Discord has a 2000 character limit.
-pub fn format_large_collection<T: std::fmt::Display>(collection: &Vec<T>) -> Vec<String> {
-    let width = collection.len() / 10;
-    let mut partitioned: Vec<String> = Vec::new();
-    let mut strings: Vec<String> = collection
+/// Format collection into a 1-indexed joint String
+pub fn format_collection<T: std::fmt::Display>(collection: &Vec<T>) -> String {
+    number_collection(collection).join("\n")
+}
+
+/// Format collection into as few 1-indexed messages as fit under Discord's character limit
+pub fn format_large_collection<T: std::fmt::Display>(collection: &[T]) -> Vec<String> {
+    paginate(&number_collection(collection))
+}
+
+/// Pack items into as few messages as fit under Discord's character limit, one item per line. An
+/// item is never split across messages, unless it's too long for a message on its own.
+pub fn paginate<T: AsRef<str>>(items: &[T]) -> Vec<String> {
+    pack(items, MESSAGE_LIMIT)
+}
+
+/// Pack items into as few pages as fit under the character limit, see [`paginate`]
+pub fn pack<T: AsRef<str>>(items: &[T], limit: usize) -> Vec<String> {
+    let mut pages: Vec<String> = Vec::new();
+    let mut page = String::new();
+    let mut length = 0;
+
+    for piece in items
         .iter()
-        .enumerate()
-        .map(|(i, item)| format!("{:>width$}.) {}", i + 1, item, width = width))
-        .collect::<Vec<String>>();
+        .flat_map(|item| split_oversized(item.as_ref().trim_end_matches('\n'), limit))
+    {
+        let piece_length = piece.chars().count();
+        if !page.is_empty() && length + 1 + piece_length > limit {
+            pages.push(std::mem::take(&mut page));
+            length = 0;
+        }
+        if !page.is_empty() {
+            page.push('\n');
+            length += 1;
+        }
+        page.push_str(&piece);
+        length += piece_length;
+    }
+    if !page.is_empty() {
+        pages.push(page);
+    }
+
+    pages
+}
+
+/// Split an item too long for one message at its line breaks, and mid-line only when a single line
+/// is too long as well
+fn split_oversized(item: &str, limit: usize) -> Vec<String> {
+    if item.chars().count() <= limit {
+        return vec![item.to_string()];
+    }
+
+    let lines: Vec<String> = item
+        .lines()
+        .flat_map(|line| {
+            let chars: Vec<char> = line.chars().collect();
+            if chars.is_empty() {
+                vec![String::new()]
+            } else {
+                chars
+                    .chunks(limit)
+                    .map(|chunk| chunk.iter().collect())
+                    .collect()
+            }
+        })
+        .collect();
+
+    pack(&lines, limit)
+}
+
+/// Send the items in as few messages as fit, see [`paginate`]. Returns the last message sent.
+pub async fn say_paginated<T: AsRef<str>>(
+    ctx: &Context,
+    channel_id: ChannelId,
+    items: &[T],
+) -> anyhow::Result<Option<Message>> {
+    let mut message = None;
+    for page in paginate(items) {
+        message = Some(channel_id.say(&ctx.http, page).await?);
+    }
+
+    Ok(message)
+}
+
+/// Add buttons to turn to the previous and next pages
+fn page_buttons(row: &mut CreateActionRow, page: usize, pages: usize) -> &mut CreateActionRow {
+    row.create_button(|button| {
+        button
+            .custom_id("previous")
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0)
+    })
+    .create_button(|button| {
+        button
+            .custom_id("next")
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= pages)
+    })
+}
+
+/// Send the items as a single message whose pages are turned with buttons, instead of a message for
+/// each page. The buttons are taken away once nobody has turned the page in a while.
+pub async fn browse_pages<T: AsRef<str>>(
+    ctx: &Context,
+    msg: &Message,
+    items: &[T],
+) -> anyhow::Result<()> {
+    let pages = pack(items, MESSAGE_LIMIT - PAGE_FOOTER_LENGTH);
+    if pages.len() <= 1 {
+        say_paginated(ctx, msg.channel_id, &pages).await?;
 
-    while !strings.is_empty() {
-        let range = if strings.len() >= 20 {
-            0..20
-        } else {
-            0..strings.len()
+        return Ok(());
+    }
+
+    let content = |page: usize| format!("{}\nPage {} of {}", pages[page], page + 1, pages.len());
+    let mut page = 0;
+    let mut message = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.content(content(page))
+                .components(|c| c.create_action_row(|row| page_buttons(row, page, pages.len())))
+        })
+        .await?;
+
+    while let Some(interaction) = message
+        .await_component_interaction(&ctx)
+        .timeout(Duration::from_secs(SELECTION_TIMEOUT))
+        .await
+    {
+        page = match interaction.data.custom_id.as_str() {
+            "previous" => page.saturating_sub(1),
+            "next" => (page + 1).min(pages.len() - 1),
+            _ => page,
         };
-        partitioned.push(strings.drain(range).collect::<Vec<String>>().join("\n"));
+        interaction
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| {
+                        d.content(content(page)).components(|c| {
+                            c.create_action_row(|row| page_buttons(row, page, pages.len()))
+                        })
+                    })
+            })
+            .await?;
     }
 
-    partitioned
+    message.edit(&ctx, |m| m.components(|c| c)).await?;
+
+    Ok(())
 }
 
-/// Ask to pick from a collection and return the id of the set
-pub async fn pick_collection<'a, T>(
+/// What the author did with a picker
+#[derive(Debug, PartialEq)]
+enum PickerAction {
+    /// Picked the item at this index of the whole collection
+    Pick(usize),
+    /// Turned to this 0-indexed page
+    Page(usize),
+    Cancel,
+}
+
+/// Read the component the author used on a picker showing the given page
+fn picker_action(
+    custom_id: &str,
+    values: &[String],
+    page: usize,
+    pages: usize,
+    len: usize,
+) -> Option<PickerAction> {
+    match custom_id {
+        "pick" => values
+            .first()
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|index| *index < len)
+            .map(PickerAction::Pick),
+        "previous" if page > 0 => Some(PickerAction::Page(page - 1)),
+        "next" if page + 1 < pages => Some(PickerAction::Page(page + 1)),
+        "cancel" => Some(PickerAction::Cancel),
+        _ => None,
+    }
+}
+
+/// Cut a label short enough for a select menu option
+fn picker_label(label: &str) -> String {
+    if label.chars().count() > MAX_LABEL_LENGTH {
+        label
+            .chars()
+            .take(MAX_LABEL_LENGTH - 1)
+            .chain(std::iter::once('…'))
+            .collect()
+    } else {
+        label.to_string()
+    }
+}
+
+/// The picker's prompt, along with which page is showing when there's more than one
+fn picker_content(prompt: &str, page: usize, pages: usize) -> String {
+    if pages > 1 {
+        format!("{}\nPage {} of {}", prompt, page + 1, pages)
+    } else {
+        prompt.to_string()
+    }
+}
+
+/// A select menu of the page's labels, with buttons to turn the page or cancel
+fn picker_components<'a>(
+    components: &'a mut CreateComponents,
+    labels: &[String],
+    page: usize,
+    pages: usize,
+) -> &'a mut CreateComponents {
+    components
+        .create_action_row(|row| {
+            row.create_select_menu(|menu| {
+                menu.custom_id("pick")
+                    .placeholder("Pick one")
+                    .options(|options| {
+                        for (i, label) in labels
+                            .iter()
+                            .enumerate()
+                            .skip(page * PICKER_PAGE_SIZE)
+                            .take(PICKER_PAGE_SIZE)
+                        {
+                            options.create_option(|option| option.label(label).value(i));
+                        }
+
+                        options
+                    })
+            })
+        })
+        .create_action_row(|row| {
+            if pages > 1 {
+                page_buttons(row, page, pages);
+            }
+
+            row.create_button(|button| {
+                button
+                    .custom_id("cancel")
+                    .label("Cancel")
+                    .style(ButtonStyle::Danger)
+            })
+        })
+}
+
+/// Ask the author to pick from a collection with a select menu, turning pages with buttons when
+/// there are too many items for one menu. Returns `None` when the author cancels or doesn't pick
+/// in time, after saying so on the picker.
+pub async fn pick<'a, T>(
     ctx: &Context,
     msg: &Message,
-    collection: &'a Vec<T>,
+    prompt: impl std::fmt::Display,
+    collection: &'a [T],
+    label: impl Fn(&T) -> String,
 ) -> anyhow::Result<Option<&'a T>> {
-    if let Some(choice) = &msg
+    let prompt = prompt.to_string();
+    if collection.is_empty() {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!("{}\nThere's nothing to pick from.", prompt),
+            )
+            .await?;
+
+        return Ok(None);
+    }
+
+    let labels: Vec<String> = collection
+        .iter()
+        .map(|item| picker_label(&label(item)))
+        .collect();
+    let pages = collection.len().div_ceil(PICKER_PAGE_SIZE);
+    let mut page = 0;
+    let mut picker = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.content(picker_content(&prompt, page, pages))
+                .components(|c| picker_components(c, &labels, page, pages))
+        })
+        .await?;
+
+    loop {
+        let interaction = match picker
+            .await_component_interaction(&ctx)
+            .author_id(msg.author.id)
+            .timeout(Duration::from_secs(SELECTION_TIMEOUT))
+            .await
+        {
+            Some(interaction) => interaction,
+            None => {
+                picker
+                    .edit(&ctx, |m| {
+                        m.content(format!("{}\nNothing was picked in time.", prompt))
+                            .components(|c| c)
+                    })
+                    .await?;
+
+                return Ok(None);
+            }
+        };
+
+        let (content, picked) = match picker_action(
+            &interaction.data.custom_id,
+            &interaction.data.values,
+            page,
+            pages,
+            collection.len(),
+        ) {
+            Some(PickerAction::Pick(index)) => (
+                format!("{}\nPicked: {}", prompt, labels[index]),
+                Some(&collection[index]),
+            ),
+            Some(PickerAction::Page(next)) => {
+                page = next;
+                interaction
+                    .create_interaction_response(&ctx.http, |r| {
+                        r.kind(InteractionResponseType::UpdateMessage)
+                            .interaction_response_data(|d| {
+                                d.content(picker_content(&prompt, page, pages))
+                                    .components(|c| picker_components(c, &labels, page, pages))
+                            })
+                    })
+                    .await?;
+
+                continue;
+            }
+            Some(PickerAction::Cancel) => (format!("{}\nCancelled.", prompt), None),
+            None => {
+                interaction
+                    .create_interaction_response(&ctx.http, |r| {
+                        r.kind(InteractionResponseType::DeferredUpdateMessage)
+                    })
+                    .await?;
+
+                continue;
+            }
+        };
+
+        interaction
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| d.content(content).components(|c| c))
+            })
+            .await?;
+
+        return Ok(picked);
+    }
+}
+
+/// Find the items matching the query. An exact code wins outright, otherwise the titles are fuzzy
+/// matched, best matches first.
+pub fn find_matches<'a, T>(
+    query: &str,
+    items: &'a [T],
+    code: impl Fn(&T) -> &str,
+    title: impl Fn(&T) -> &str,
+) -> Vec<&'a T> {
+    let query = query.trim().trim_matches('"');
+    match items
+        .iter()
+        .find(|item| code(item).eq_ignore_ascii_case(query))
+    {
+        Some(item) => vec![item],
+        None => fuzzy::find_matches(query, items, title),
+    }
+}
+
+/// Pick one of the matches for the query. When there are several, ask which one was meant.
+pub async fn pick_match<'a, T>(
+    ctx: &Context,
+    msg: &Message,
+    query: &str,
+    matches: Vec<&'a T>,
+    code: impl Fn(&T) -> &str,
+    title: impl Fn(&T) -> &str,
+) -> anyhow::Result<Option<&'a T>> {
+    if matches.len() <= 1 {
+        return Ok(matches.first().copied());
+    }
+
+    Ok(pick(
+        ctx,
+        msg,
+        format!(
+            "Several matched '{}', which one did you mean?",
+            query.trim().trim_matches('"')
+        ),
+        &matches,
+        |item| format!("{} (Code: {})", title(item), code(item)),
+    )
+    .await?
+    .copied())
+}
+
+/// Format matches as a collection of their titles and codes
+pub fn format_matches<T>(
+    matches: &[&T],
+    code: impl Fn(&T) -> &str,
+    title: impl Fn(&T) -> &str,
+) -> String {
+    format_collection(
+        &matches
+            .iter()
+            .map(|item| format!("{} (Code: {})", title(item), code(item)))
+            .collect(),
+    )
+}
+
+/// Find an item by its exact code, falling back to fuzzy matching its title. When several titles
+/// match, ask to pick one of them.
+pub async fn resolve<'a, T>(
+    ctx: &Context,
+    msg: &Message,
+    query: &str,
+    items: &'a [T],
+    code: impl Fn(&T) -> &str + Copy,
+    title: impl Fn(&T) -> &str + Copy,
+) -> anyhow::Result<Option<&'a T>> {
+    pick_match(
+        ctx,
+        msg,
+        query,
+        find_matches(query, items, code, title),
+        code,
+        title,
+    )
+    .await
+}
+
+/// The arguments with any mentions left out, i.e. a title followed by the players who completed it
+pub fn strip_mentions(input: &str) -> String {
+    input
+        .split_whitespace()
+        .filter(|word| serenity::utils::parse_mention(word).is_none())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Ask a question and return the number the author replies with
+pub async fn ask_number(
+    ctx: &Context,
+    msg: &Message,
+    question: impl std::fmt::Display,
+) -> anyhow::Result<Option<i32>> {
+    msg.channel_id.say(&ctx.http, question).await?;
+
+    if let Some(answer) = &msg
         .author
         .await_reply(&ctx)
         .timeout(Duration::from_secs(SELECTION_TIMEOUT))
         .await
     {
-        let number = choice.content.parse::<usize>()?;
-        let index = number - 1;
-
-        Ok(collection.get(index))
+        Ok(answer.content.trim().parse::<i32>().ok())
     } else {
         Ok(None)
     }
 }
 
-/// Ask to pick from all sets and return the set id picked
-pub async fn pick_sets(ctx: &Context, msg: &Message) -> anyhow::Result<Option<i64>> {
+/// Ask to pick from all sets and return the set picked
+pub async fn pick_sets(ctx: &Context, msg: &Message) -> anyhow::Result<Option<Set>> {
     let data = ctx.data.read().await;
     let pool = data
         .get::<PostgresPool>()
         .expect("Expected PostgresPool in TypeMap.");
     let sets = Set::find_all(pool).await?;
-    msg.channel_id
-        .say(
-            &ctx.http,
-            format!(
-                "Here are all the sets:\n{}",
-                format_collection(&sets.iter().map(|set| &set.name).collect())
-            ),
-        )
-        .await?;
 
-    let set_id = pick_collection(ctx, msg, &sets).await?.map(|set| set.id);
-    Ok(set_id)
+    let set = pick(ctx, msg, "Which set?", &sets, |set| set.to_string())
+        .await?
+        .cloned();
+    Ok(set)
 }
 
 /// Checks that a message successfully sent; if not, then logs why to stderr.
@@ -94,37 +517,97 @@ pub fn check_msg(result: serenity::Result<Message>) {
     }
 }
 
-/// Display Challenges grouped by Scenario
-pub fn format_challenges_by_scenario<'a>(
-    scenarios: impl std::iter::Iterator<Item = (&'a Scenario, &'a Vec<Challenge>)> + Clone,
-) -> Vec<String> {
-    let width = scenarios.clone().count() / 10;
-    scenarios
-        .into_iter()
-        .enumerate()
-        .map(|(i, (scenario, challenges))| {
-            let mut content = MessageBuilder::new();
-
-            content.push(format!(
-                "{:>width$}.) {}\n",
-                i + 1,
-                scenario.title,
-                width = width
-            ));
-            for challenge in challenges.into_iter() {
-                content.push(format!(
-                    "- (Code: **{}**) *{}* - {}\n",
-                    challenge.code,
-                    challenge.name,
-                    challenge
-                        .description
-                        .as_ref()
-                        .map(|s| String::from(s))
-                        .unwrap_or_else(|| String::from(""))
-                ));
-            }
+/// Logs why something the command doesn't depend on failed, instead of failing the command.
+pub fn log_failure<T>(result: anyhow::Result<T>, action: &str) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(why) => {
+            error!("Unable to {}: {:?}", action, why);
 
-            content.build()
-        })
-        .collect()
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_strips_mentions() {
+        assert_eq!(strip_mentions("Cut off <@123> <@!456>"), "Cut off");
+        assert_eq!(strip_mentions("<@123> CON1901"), "CON1901");
+    }
+
+    #[test]
+    fn it_reads_picker_actions() {
+        let values = vec!["30".to_string()];
+
+        assert_eq!(
+            picker_action("pick", &values, 1, 2, 40),
+            Some(PickerAction::Pick(30))
+        );
+        assert_eq!(picker_action("pick", &values, 1, 2, 30), None);
+        assert_eq!(picker_action("pick", &[], 0, 1, 10), None);
+        assert_eq!(
+            picker_action("next", &[], 0, 2, 40),
+            Some(PickerAction::Page(1))
+        );
+        assert_eq!(picker_action("next", &[], 1, 2, 40), None);
+        assert_eq!(
+            picker_action("previous", &[], 1, 2, 40),
+            Some(PickerAction::Page(0))
+        );
+        assert_eq!(picker_action("previous", &[], 0, 2, 40), None);
+        assert_eq!(
+            picker_action("cancel", &[], 0, 1, 10),
+            Some(PickerAction::Cancel)
+        );
+    }
+
+    #[test]
+    fn it_cuts_picker_labels_short() {
+        assert_eq!(picker_label("Khazad-dûm"), "Khazad-dûm");
+
+        let label = picker_label(&"a".repeat(150));
+        assert_eq!(label.chars().count(), MAX_LABEL_LENGTH);
+        assert!(label.ends_with('…'));
+    }
+
+    #[test]
+    fn it_paginates_by_length() {
+        let items: Vec<String> = (0..30).map(|i| format!("{:0>99}", i)).collect();
+        let pages = paginate(&items);
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].lines().count(), 20);
+        assert_eq!(pages[0].chars().count(), 1999);
+        assert_eq!(pages[1].lines().count(), 10);
+        assert!(paginate::<&str>(&[]).is_empty());
+    }
+
+    #[test]
+    fn it_never_splits_an_item() {
+        let items = ["a".repeat(1500), "b\n".repeat(300)];
+        let pages = paginate(&items);
+
+        assert_eq!(
+            pages,
+            vec!["a".repeat(1500), "b\n".repeat(300).trim_end().to_string()]
+        );
+    }
+
+    #[test]
+    fn it_splits_an_item_too_long_for_a_message() {
+        let line = "c".repeat(1500);
+        let pages = paginate(&[format!("{}\n{}", line, line), "d".repeat(4500)]);
+
+        assert_eq!(
+            pages
+                .iter()
+                .map(|page| page.chars().count())
+                .collect::<Vec<usize>>(),
+            vec![1500, 1500, 2000, 2000, 500]
+        );
+    }
 }